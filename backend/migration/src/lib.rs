@@ -2,6 +2,19 @@ pub use sea_orm_migration::prelude::*;
 
 mod m20231207_000001_create_table;
 mod m20240821_000001_create_tag;
+mod m20241018_000001_problem_io_file;
+mod m20241018_000002_submit_stderr;
+mod m20241018_000003_submit_exit_status;
+mod m20241019_000001_contest_rule;
+mod m20241019_000002_contest_freeze;
+mod m20241020_000001_contest_register;
+mod m20241020_000002_create_team;
+mod m20241020_000003_user_contest_team;
+mod m20241020_000004_user_contest_virtual;
+mod m20241020_000005_create_clarification;
+mod m20241020_000006_create_problem_revision;
+mod m20241020_000007_submit_revision;
+mod m20241021_000001_problem_checker;
 
 pub struct Migrator;
 
@@ -11,6 +24,19 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20231207_000001_create_table::Migration),
             Box::new(m20240821_000001_create_tag::Migration),
+            Box::new(m20241018_000001_problem_io_file::Migration),
+            Box::new(m20241018_000002_submit_stderr::Migration),
+            Box::new(m20241018_000003_submit_exit_status::Migration),
+            Box::new(m20241019_000001_contest_rule::Migration),
+            Box::new(m20241019_000002_contest_freeze::Migration),
+            Box::new(m20241020_000001_contest_register::Migration),
+            Box::new(m20241020_000002_create_team::Migration),
+            Box::new(m20241020_000003_user_contest_team::Migration),
+            Box::new(m20241020_000004_user_contest_virtual::Migration),
+            Box::new(m20241020_000005_create_clarification::Migration),
+            Box::new(m20241020_000006_create_problem_revision::Migration),
+            Box::new(m20241020_000007_submit_revision::Migration),
+            Box::new(m20241021_000001_problem_checker::Migration),
         ]
     }
 }
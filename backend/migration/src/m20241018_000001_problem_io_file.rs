@@ -0,0 +1,34 @@
+use crate::m20231207_000001_create_table::Problem;
+use sea_orm_migration::prelude::*;
+
+#[derive(Iden)]
+enum ProblemIoFile {
+    InputFile,
+    OutputFile,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // sqlite only support one column per `ALTER TABLE`
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Problem::Table)
+                    .add_column(ColumnDef::new(ProblemIoFile::InputFile).string().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Problem::Table)
+                    .add_column(ColumnDef::new(ProblemIoFile::OutputFile).string().null())
+                    .to_owned(),
+            )
+            .await
+    }
+}
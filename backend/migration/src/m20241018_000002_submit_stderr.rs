@@ -0,0 +1,24 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(Iden)]
+enum Submit {
+    Table,
+    Stderr,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submit::Table)
+                    .add_column(ColumnDef::new(Submit::Stderr).binary().null())
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,34 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(Iden)]
+enum Submit {
+    Table,
+    ExitCode,
+    Signal,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // sqlite doesn't support multiple alter options in one statement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submit::Table)
+                    .add_column(ColumnDef::new(Submit::ExitCode).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Submit::Table)
+                    .add_column(ColumnDef::new(Submit::Signal).integer().null())
+                    .to_owned(),
+            )
+            .await
+    }
+}
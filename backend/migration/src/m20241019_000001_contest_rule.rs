@@ -0,0 +1,29 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(Iden)]
+enum Contest {
+    Table,
+    Rule,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .add_column(
+                        ColumnDef::new(Contest::Rule)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
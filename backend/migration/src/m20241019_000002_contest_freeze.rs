@@ -0,0 +1,24 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(Iden)]
+enum Contest {
+    Table,
+    FreezeAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .add_column(ColumnDef::new(Contest::FreezeAt).date_time().null())
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,34 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(Iden)]
+enum Contest {
+    Table,
+    RegisterBegin,
+    RegisterEnd,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // sqlite only accept one column per alter statement
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .add_column(ColumnDef::new(Contest::RegisterBegin).date_time().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Contest::Table)
+                    .add_column(ColumnDef::new(Contest::RegisterEnd).date_time().null())
+                    .to_owned(),
+            )
+            .await
+    }
+}
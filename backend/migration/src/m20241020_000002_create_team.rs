@@ -0,0 +1,130 @@
+use sea_orm_migration::prelude::*;
+
+static CREATE_AT: &str = "DEFAULT CURRENT_TIMESTAMP";
+
+#[derive(Iden)]
+enum Contest {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Team {
+    Table,
+    Id,
+    ContestId,
+    Name,
+    CreateAt,
+}
+
+#[derive(Iden)]
+enum ContestInvite {
+    Table,
+    Id,
+    ContestId,
+    TeamId,
+    Code,
+    ByHost,
+    CreateAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Team::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Team::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Team::ContestId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-team-contest")
+                            .from(Team::Table, Team::ContestId)
+                            .to(Contest::Table, Contest::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(Team::Name).string().not_null())
+                    .col(
+                        ColumnDef::new(Team::CreateAt)
+                            .date_time()
+                            .not_null()
+                            .extra(CREATE_AT.to_string()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .create_table(
+                Table::create()
+                    .table(ContestInvite::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ContestInvite::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ContestInvite::ContestId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-invite-contest")
+                            .from(ContestInvite::Table, ContestInvite::ContestId)
+                            .to(Contest::Table, Contest::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(ContestInvite::TeamId).integer().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-invite-team")
+                            .from(ContestInvite::Table, ContestInvite::TeamId)
+                            .to(Team::Table, Team::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(ContestInvite::Code)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ContestInvite::ByHost)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(ContestInvite::CreateAt)
+                            .date_time()
+                            .not_null()
+                            .extra(CREATE_AT.to_string()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ContestInvite::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(Team::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,38 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(Iden)]
+enum UserContest {
+    Table,
+    TeamId,
+    Banned,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserContest::Table)
+                    .add_column(ColumnDef::new(UserContest::TeamId).integer().null())
+                    .to_owned(),
+            )
+            .await?;
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(UserContest::Table)
+                    .add_column(
+                        ColumnDef::new(UserContest::Banned)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+}
@@ -0,0 +1,108 @@
+use sea_orm_migration::prelude::*;
+
+static CREATE_AT: &str = "DEFAULT CURRENT_TIMESTAMP";
+
+#[derive(Iden)]
+enum Contest {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Problem {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum Clarification {
+    Table,
+    Id,
+    ContestId,
+    ProblemId,
+    UserId,
+    Question,
+    Answer,
+    Public,
+    CreateAt,
+    AnswerAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Clarification::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Clarification::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Clarification::ContestId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-clarification-contest")
+                            .from(Clarification::Table, Clarification::ContestId)
+                            .to(Contest::Table, Contest::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(Clarification::ProblemId).integer().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-clarification-problem")
+                            .from(Clarification::Table, Clarification::ProblemId)
+                            .to(Problem::Table, Problem::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .col(ColumnDef::new(Clarification::UserId).integer().not_null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-clarification-user")
+                            .from(Clarification::Table, Clarification::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(ColumnDef::new(Clarification::Question).string().not_null())
+                    .col(ColumnDef::new(Clarification::Answer).string().null())
+                    .col(
+                        ColumnDef::new(Clarification::Public)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(Clarification::CreateAt)
+                            .date_time()
+                            .not_null()
+                            .extra(CREATE_AT.to_string()),
+                    )
+                    .col(ColumnDef::new(Clarification::AnswerAt).date_time().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Clarification::Table).to_owned())
+            .await
+    }
+}
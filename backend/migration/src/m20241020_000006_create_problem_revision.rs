@@ -0,0 +1,169 @@
+use sea_orm_migration::prelude::*;
+
+static CREATE_AT: &str = "DEFAULT CURRENT_TIMESTAMP";
+
+#[derive(Iden)]
+enum Problem {
+    Table,
+    Id,
+    UserId,
+    Title,
+    Content,
+    Time,
+    Memory,
+    MatchRule,
+    InputFile,
+    OutputFile,
+}
+
+#[derive(Iden)]
+enum User {
+    Table,
+    Id,
+}
+
+#[derive(Iden)]
+enum ProblemRevision {
+    Table,
+    Id,
+    ProblemId,
+    Revision,
+    UserId,
+    Title,
+    Content,
+    Time,
+    Memory,
+    MatchRule,
+    InputFile,
+    OutputFile,
+    CreateAt,
+}
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ProblemRevision::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ProblemRevision::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ProblemRevision::ProblemId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-revision-problem")
+                            .from(ProblemRevision::Table, ProblemRevision::ProblemId)
+                            .to(Problem::Table, Problem::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .col(
+                        ColumnDef::new(ProblemRevision::Revision)
+                            .unsigned()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ProblemRevision::UserId).integer().null())
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-revision-user")
+                            .from(ProblemRevision::Table, ProblemRevision::UserId)
+                            .to(User::Table, User::Id)
+                            .on_delete(ForeignKeyAction::SetNull),
+                    )
+                    .col(ColumnDef::new(ProblemRevision::Title).string().not_null())
+                    .col(ColumnDef::new(ProblemRevision::Content).string().not_null())
+                    .col(
+                        ColumnDef::new(ProblemRevision::Time)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProblemRevision::Memory)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ProblemRevision::MatchRule)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ProblemRevision::InputFile).string().null())
+                    .col(ColumnDef::new(ProblemRevision::OutputFile).string().null())
+                    .col(
+                        ColumnDef::new(ProblemRevision::CreateAt)
+                            .date_time()
+                            .not_null()
+                            .extra(CREATE_AT.to_string()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+        // revision number is unique per problem
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-problem_revision-problem_id-revision")
+                    .table(ProblemRevision::Table)
+                    .col(ProblemRevision::ProblemId)
+                    .col(ProblemRevision::Revision)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+        // existing problems start from revision 1, authored by uploader
+        let snapshot = Query::select()
+            .column(Problem::Id)
+            .expr(Expr::val(1))
+            .columns([
+                Problem::UserId,
+                Problem::Title,
+                Problem::Content,
+                Problem::Time,
+                Problem::Memory,
+                Problem::MatchRule,
+                Problem::InputFile,
+                Problem::OutputFile,
+            ])
+            .from(Problem::Table)
+            .to_owned();
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(ProblemRevision::Table)
+                    .columns([
+                        ProblemRevision::ProblemId,
+                        ProblemRevision::Revision,
+                        ProblemRevision::UserId,
+                        ProblemRevision::Title,
+                        ProblemRevision::Content,
+                        ProblemRevision::Time,
+                        ProblemRevision::Memory,
+                        ProblemRevision::MatchRule,
+                        ProblemRevision::InputFile,
+                        ProblemRevision::OutputFile,
+                    ])
+                    .select_from(snapshot)
+                    .map_err(|err| DbErr::Migration(err.to_string()))?
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ProblemRevision::Table).to_owned())
+            .await
+    }
+}
@@ -3,8 +3,9 @@ use crate::server::InitError;
 use futures::executor::block_on;
 use ip_network::IpNetwork;
 use serde::{Deserialize, Serialize};
-use std::{path::PathBuf, str::FromStr};
+use std::{net::SocketAddr, path::PathBuf, str::FromStr};
 use tokio::{fs, io::AsyncReadExt};
+use tonic::transport::{Certificate, ClientTlsConfig, Identity};
 
 lazy_static::lazy_static! {
     pub static ref CONFIG_PATH: PathBuf=PathBuf::from_str(
@@ -42,6 +43,15 @@ pub struct GlobalConfig {
     pub imgur: Imgur,
     #[serde(default)]
     pub default_role: Option<ConfigRole>,
+    /// address to serve prometheus-style `/metrics`, disable if not set
+    #[serde(default = "default_metrics")]
+    pub metrics: Option<SocketAddr>,
+    #[serde(default)]
+    pub playground: Playground,
+}
+
+fn default_metrics() -> Option<SocketAddr> {
+    Some(SocketAddr::from_str("127.0.0.1:9091").unwrap())
 }
 
 fn default_opentelemetry() -> Option<String> {
@@ -56,6 +66,7 @@ fn default_judger() -> Vec<Judger> {
         name: "http://127.0.0.1:8080".to_owned(),
         secret: None,
         judger_type: JudgerType::Static,
+        tls: None,
     }]
 }
 
@@ -87,6 +98,46 @@ pub struct Judger {
     pub secret: Option<String>,
     #[serde(rename = "type")]
     pub judger_type: JudgerType,
+    /// connect to judger over plaintext if not set
+    #[serde(default)]
+    pub tls: Option<JudgerTls>,
+}
+
+/// TLS option for connection to judger
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct JudgerTls {
+    /// CA to verify judger's certificate, use system roots if not set
+    #[serde(default)]
+    pub ca: Option<PathBuf>,
+    /// client certificate in pem, required by judger with `client_ca` set
+    #[serde(default)]
+    pub cert: Option<PathBuf>,
+    /// client private key in pem
+    #[serde(default)]
+    pub key: Option<PathBuf>,
+    /// domain name to verify judger's certificate against
+    #[serde(default)]
+    pub domain: Option<String>,
+}
+
+impl JudgerTls {
+    pub async fn load(&self) -> std::io::Result<ClientTlsConfig> {
+        let mut config = ClientTlsConfig::new();
+        if let Some(ca) = &self.ca {
+            config = config.ca_certificate(Certificate::from_pem(fs::read(ca).await?));
+        }
+        if let (Some(cert), Some(key)) = (&self.cert, &self.key) {
+            config = config.identity(Identity::from_pem(
+                fs::read(cert).await?,
+                fs::read(key).await?,
+            ));
+        }
+        if let Some(domain) = &self.domain {
+            config = config.domain_name(domain);
+        }
+        Ok(config)
+    }
 }
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "lowercase", deny_unknown_fields)]
@@ -94,6 +145,8 @@ pub enum JudgerType {
     Docker,
     Static,
     LoadBalanced,
+    /// `name` is path to a toml file or url of a http registry listing judgers
+    Registry,
 }
 
 impl Default for JudgerType {
@@ -121,6 +174,25 @@ impl Default for GrpcOption {
     }
 }
 
+/// resource limit of `Playground.Run`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Playground {
+    /// memory limit in byte
+    pub memory: u64,
+    /// cpu time limit in nanosecond
+    pub time: u64,
+}
+
+impl Default for Playground {
+    fn default() -> Self {
+        Self {
+            memory: 256 * 1024 * 1024,
+            time: 1_000_000_000,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct Imgur {
@@ -0,0 +1,28 @@
+//! notification of clarification
+use tokio::sync::broadcast::Receiver;
+
+use super::judger::pubsub::Topics;
+use crate::entity::clarification::Model;
+
+/// broadcast clarifications by contest
+#[derive(Default)]
+pub struct ClarificationController {
+    topics: Topics<Model, i32>,
+}
+
+impl ClarificationController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// notify subscribers of the contest, it's dropped if there is none
+    pub fn publish(&self, model: Model) {
+        let contest_id = model.contest_id;
+        self.topics.send(&contest_id, model);
+    }
+    /// subscribe every clarification of the contest
+    ///
+    /// It's subscriber's responsibility to filter out invisible ones.
+    pub fn subscribe(&self, contest_id: i32) -> Receiver<Model> {
+        self.topics.subscribe(contest_id)
+    }
+}
@@ -1,24 +1,32 @@
-mod pubsub;
+pub(crate) mod pubsub;
 mod route;
 mod score;
 
+use dashmap::DashMap;
 use std::{ops::Deref, sync::Arc};
-use tokio_stream::StreamExt;
+use tokio::sync::{mpsc, OnceCell};
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
 
 use crate::{report_internal, TonicStream};
 use grpc::backend::StateCode as BackendCode;
-use sea_orm::{ActiveModelTrait, ActiveValue, DatabaseConnection, EntityTrait, QueryOrder};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect,
+};
 use thiserror::Error;
 use tonic::Status;
 use tracing::{instrument, Instrument};
 use uuid::Uuid;
 
-use self::{pubsub::PubSub, route::*};
+use self::{
+    pubsub::{PubGuard, PubSub, Topics},
+    route::*,
+};
 use crate::config::CONFIG;
 use crate::entity::*;
 use crate::util::code::Code;
 use grpc::{
-    backend::{submit_status, SubmitStatus},
+    backend::{playground_result, submit_status, PlaygroundResult, SubmitStatus},
     judger::*,
 };
 
@@ -44,6 +52,10 @@ pub enum Error {
     DnsResolve(#[from] hickory_resolver::error::ResolveError),
     #[error("uri parse failed: should be in format of `http://ip:port`")]
     UriParse,
+    #[error("fail to read tls certificate: `{0}`")]
+    Certificate(#[from] std::io::Error),
+    #[error("playground session not found")]
+    SessionNotFound,
 }
 
 impl From<Status> for Error {
@@ -68,6 +80,8 @@ impl From<Error> for Status {
             Error::RateLimit => Status::resource_exhausted("resource limit imposed by backend"),
             Error::DnsResolve(x) => report_internal!(warn, "{}", x),
             Error::UriParse => report_internal!(warn, "uri parse failed"),
+            Error::Certificate(x) => report_internal!(warn, "{}", x),
+            Error::SessionNotFound => Status::not_found("playground session not found"),
         }
     }
 }
@@ -92,7 +106,24 @@ impl From<Code> for SubmitStatus {
     }
 }
 
-#[allow(dead_code)]
+/// submission event of a contest, see [`Judger::watch`]
+#[derive(Clone)]
+pub struct ContestEvent {
+    pub submit_id: i32,
+    pub user_id: i32,
+    pub problem_id: i32,
+    pub upload_at: chrono::NaiveDateTime,
+    /// unset for new submission
+    pub status: Option<Code>,
+    /// contest read after the event, so change of freeze time is seen by
+    /// existing subscribers, shared like `board`
+    pub contest: Arc<OnceCell<contest::Model>>,
+    /// scoreboard after the event, indexed by whether it's frozen
+    ///
+    /// It's shared by subscribers, so it's computed at most once per freeze state
+    pub board: Arc<[OnceCell<Arc<Vec<scoreboard::Row>>>; 2]>,
+}
+
 pub struct PlaygroundPayload {
     pub input: Vec<u8>,
     pub code: Vec<u8>,
@@ -104,9 +135,21 @@ pub struct PlaygroundPayload {
 pub struct Judger {
     router: Arc<Router>,
     pubsub: Arc<PubSub<Result<SubmitStatus, Status>, i32>>,
+    contest: Topics<ContestEvent, i32>,
+    /// stdin of running playground and its owner, keyed by session id
+    playground: DashMap<Uuid, (i32, mpsc::Sender<Vec<u8>>)>,
     db: Arc<DatabaseConnection>,
 }
 
+/// remove playground session once its output stream is dropped
+struct PlaygroundGuard(Arc<Judger>, Uuid);
+
+impl Drop for PlaygroundGuard {
+    fn drop(&mut self) {
+        self.0.playground.remove(&self.1);
+    }
+}
+
 impl Judger {
     #[tracing::instrument(name = "judger_construct", level = "info", skip_all)]
     pub async fn new(db: Arc<DatabaseConnection>) -> Result<Self, Error> {
@@ -115,24 +158,27 @@ impl Judger {
         Ok(Judger {
             router,
             pubsub: Arc::new(PubSub::default()),
+            contest: Topics::default(),
+            playground: DashMap::default(),
             db,
         })
     }
     /// helper for streaming and process result(judge) from judger
-    #[instrument(skip(self, stream, model, scores))]
+    #[instrument(skip(self, stream, model, scores, tx))]
     async fn stream(
         self: Arc<Self>,
         mut stream: tonic::Streaming<JudgeResponse>,
         mut model: submit::ActiveModel,
         scores: Vec<u32>,
+        tx: PubGuard<Result<SubmitStatus, Status>, i32>,
     ) -> Result<submit::Model, Error> {
-        let tx = self.pubsub.publish(*model.id.as_ref());
-
         let mut pass_case = 0;
         let mut status = Code::Accepted;
         let mut total_score = 0;
         let mut total_time = 0;
         let mut total_memory = 0;
+        let mut stderr = None;
+        let (mut exit_code, mut signal) = (None, None);
 
         for score in scores.into_iter().rev() {
             let res = stream
@@ -143,9 +189,12 @@ impl Judger {
             total_memory += res.memory;
             total_time += res.time;
             total_score += score;
-            let res = res.status();
-            if res != JudgerCode::Ac {
-                status = res.into();
+            let code = res.status();
+            if code != JudgerCode::Ac {
+                status = code.into();
+                stderr = res.stderr;
+                exit_code = res.exit_code;
+                signal = res.signal;
                 break;
             }
             pass_case += 1;
@@ -169,6 +218,9 @@ impl Judger {
         model.time = ActiveValue::Set(Some(total_time.try_into().unwrap_or(i64::MAX)));
         model.memory = ActiveValue::Set(Some(total_memory.try_into().unwrap_or(i64::MAX)));
         model.accept = ActiveValue::Set(status == Code::Accepted);
+        model.stderr = ActiveValue::Set(stderr);
+        model.exit_code = ActiveValue::Set(exit_code);
+        model.signal = ActiveValue::Set(signal);
 
         model
             .update(self.db.deref())
@@ -186,22 +238,48 @@ impl Judger {
             .all(db.as_ref())
             .await?;
         let (problem, testcases) = binding.pop().ok_or(Error::BadArgument("problem id"))?;
+        let revision_id: Option<i32> = problem_revision::Entity::find()
+            .filter(problem_revision::Column::ProblemId.eq(problem.id))
+            .order_by_desc(problem_revision::Column::Revision)
+            .select_only()
+            .column(problem_revision::Column::Id)
+            .into_tuple()
+            .one(db.as_ref())
+            .await?;
 
         // create uncommited submit
         let submit_model = submit::ActiveModel {
             user_id: ActiveValue::Set(Some(req.user)),
-            problem_id: ActiveValue::Set(req.user),
+            problem_id: ActiveValue::Set(req.problem),
             committed: ActiveValue::Set(false),
             lang: ActiveValue::Set(req.lang.clone().to_string()),
             code: ActiveValue::Set(req.code.clone()),
             memory: ActiveValue::Set(Some(req.memory_limit)),
             public: ActiveValue::Set(problem.public),
+            revision_id: ActiveValue::Set(revision_id),
             ..Default::default()
         }
         .save(db.as_ref())
         .await?;
 
         let submit_id = *submit_model.id.as_ref();
+        // publish before judging, so queued submit can be followed
+        let tx = self.pubsub.publish(submit_id);
+        tracing::info!(monotonic_counter.judger.submit = 1, lang = %req.lang);
+
+        let contest_id = problem.contest_id;
+        let mut event = ContestEvent {
+            submit_id,
+            user_id: req.user,
+            problem_id: req.problem,
+            upload_at: *submit_model.upload_at.as_ref(),
+            status: None,
+            contest: Default::default(),
+            board: Default::default(),
+        };
+        if let Some(contest_id) = contest_id {
+            self.contest.send(&contest_id, event.clone());
+        }
 
         let scores = testcases.iter().map(|x| x.score).collect::<Vec<_>>();
 
@@ -223,15 +301,28 @@ impl Judger {
                 time: req.time_limit as u64,
                 rule: problem.match_rule,
                 tests,
+                input_file: problem.input_file.clone().filter(|x| !x.is_empty()),
+                output_file: problem.output_file.clone().filter(|x| !x.is_empty()),
             })
             .await?;
 
         conn.report_success();
 
+        tracing::info!(counter.judger.queue = 1);
         let self_ = self.clone();
         tokio::spawn(async move {
-            match self_.stream(res.into_inner(), submit_model, scores).await {
+            let result = self_
+                .stream(res.into_inner(), submit_model, scores, tx)
+                .await;
+            tracing::info!(counter.judger.queue = -1);
+            match result {
                 Ok(submit) => {
+                    if let Some(contest_id) = contest_id {
+                        event.status = submit.status.and_then(|x| Code::try_from(x).ok());
+                        event.contest = Default::default();
+                        event.board = Default::default();
+                        self_.contest.send(&contest_id, event);
+                    }
                     score::ScoreUpload::new(req.user, problem, submit)
                         .upload(&db)
                         .await;
@@ -244,6 +335,75 @@ impl Judger {
 
         Ok(submit_id)
     }
+    /// run code interactively on judger
+    ///
+    /// The first message is session id, stdin can be sent by [`Judger::send_stdin`]
+    /// with it, and output is streamed back as it's produced
+    pub async fn playground(
+        self: &Arc<Self>,
+        user_id: i32,
+        payload: PlaygroundPayload,
+    ) -> Result<TonicStream<PlaygroundResult>, Error> {
+        let mut conn = self.router.get(&payload.lang).await?;
+
+        let start = ExecInput {
+            request: Some(exec_input::Request::Start(ExecRequest {
+                lang_uid: payload.lang.to_string(),
+                code: payload.code,
+                memory: CONFIG.playground.memory,
+                time: CONFIG.playground.time,
+                input: payload.input,
+            })),
+        };
+        let (tx, rx) = mpsc::channel(16);
+        let stdin = ReceiverStream::new(rx).map(|x| ExecInput {
+            request: Some(exec_input::Request::Stdin(x)),
+        });
+
+        let res = conn
+            .exec_interactive(tokio_stream::once(start).chain(stdin))
+            .await?;
+        conn.report_success();
+        tracing::info!(monotonic_counter.judger.playground = 1, lang = %payload.lang);
+
+        let session = Uuid::new_v4();
+        self.playground.insert(session, (user_id, tx));
+        let guard = PlaygroundGuard(self.clone(), session);
+
+        let head = PlaygroundResult {
+            result: Some(playground_result::Result::SessionId(session.to_string())),
+            stderr: None,
+        };
+        Ok(Box::pin(tokio_stream::once(Ok(head)).chain(
+            res.into_inner().map(move |x| {
+                let _ = &guard;
+                x.map(PlaygroundResult::from)
+            }),
+        )))
+    }
+    /// send chunk of stdin to playground of the user, empty chunk closes stdin
+    pub async fn send_stdin(
+        &self,
+        user_id: i32,
+        session: Uuid,
+        chunk: Vec<u8>,
+    ) -> Result<(), Error> {
+        let tx = match self.playground.get(&session) {
+            Some(x) if x.0 == user_id => x.1.clone(),
+            _ => return Err(Error::SessionNotFound),
+        };
+        if chunk.is_empty() {
+            self.playground.remove(&session);
+            return Ok(());
+        }
+        tx.send(chunk).await.map_err(|_| Error::SessionNotFound)
+    }
+    /// subscribe submission events of a contest
+    ///
+    /// each submission emit an event on create, and another one with verdict
+    pub fn watch(&self, contest_id: i32) -> tokio::sync::broadcast::Receiver<ContestEvent> {
+        self.contest.subscribe(contest_id)
+    }
     /// abstraction for publish-subscribe
     pub fn follow(&self, submit_id: i32) -> Option<TonicStream<SubmitStatus>> {
         self.pubsub.subscribe(&submit_id)
@@ -92,3 +92,49 @@ where
         })
     }
 }
+
+/// capacity of each topic in [`Topics`]
+const TOPIC_CAPACITY: usize = 64;
+
+/// publish-subscribe with long-lived topics
+///
+/// Unlike [`PubSub`], topic is created by subscriber,
+/// and message published to topic without subscriber is dropped.
+pub struct Topics<M, I> {
+    senders: Mutex<HashMap<I, Sender<M>>>,
+}
+
+impl<M, I> Default for Topics<M, I> {
+    fn default() -> Self {
+        Topics {
+            senders: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl<M, I> Topics<M, I>
+where
+    M: Clone + Send + 'static,
+    I: Eq + Clone + Hash + Send + 'static,
+{
+    /// publish message to topic [`I`]
+    pub fn send(&self, id: &I, msg: M) {
+        let mut senders = self.senders.lock();
+        if let Some(tx) = senders.get(id) {
+            if tx.send(msg).is_err() {
+                // every subscriber is gone
+                senders.remove(id);
+            }
+        }
+    }
+    /// subscribe topic [`I`]
+    ///
+    /// Receiver may lag if it doesn't keep up with [`TOPIC_CAPACITY`]
+    pub fn subscribe(&self, id: I) -> Receiver<M> {
+        self.senders
+            .lock()
+            .entry(id)
+            .or_insert_with(|| channel(TOPIC_CAPACITY).0)
+            .subscribe()
+    }
+}
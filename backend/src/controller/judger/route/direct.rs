@@ -1,20 +1,24 @@
 use super::{ConnectionDetail, Error, Routable, RouteStatus};
-use crate::config::Judger;
+use crate::config::{Judger, JudgerTls};
 use tonic::transport::Uri;
 
 /// Upstream source for static(only emit once)
 pub struct StaticRouter<const REUSE: bool> {
     uri: Option<String>,
     secret: Option<String>,
+    tls: Option<JudgerTls>,
 }
 
 #[tonic::async_trait]
 impl<const REUSE: bool> Routable for StaticRouter<REUSE> {
+    const REQUIRED: bool = true;
+
     async fn route(&mut self) -> Result<RouteStatus, Error> {
         Ok(match self.uri.take() {
             Some(x) => RouteStatus::NewConnection(ConnectionDetail {
                 uri: x,
                 secret: self.secret.clone(),
+                tls: self.tls.clone(),
                 reuse: REUSE,
             }),
             None => RouteStatus::Never,
@@ -26,6 +30,7 @@ impl<const REUSE: bool> Routable for StaticRouter<REUSE> {
         Ok(Self {
             uri: Some(config.name),
             secret: config.secret,
+            tls: config.tls,
         })
     }
 }
@@ -1,23 +1,32 @@
 pub mod direct;
+pub mod registry;
 pub mod swarm;
 
 use super::Error;
 use std::{
+    collections::{HashMap, HashSet},
     ops::DerefMut,
     sync::{
-        atomic::{AtomicIsize, Ordering},
+        atomic::{AtomicBool, AtomicIsize, Ordering},
         Arc, Weak,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use crossbeam_queue::SegQueue;
 use dashmap::{DashMap, DashSet};
-use tonic::{service::Interceptor, *};
+use opentelemetry::{
+    metrics::{ObservableGauge, Unit},
+    propagation::Injector,
+    KeyValue,
+};
+use tonic::{metadata::MetadataMap, service::Interceptor, *};
 use tracing::{debug_span, instrument, Instrument, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
 use uuid::Uuid;
 
 use crate::config::{self, Judger as JudgeConfig};
+use crate::server::metrics::meter;
 use grpc::judger::{judger_client::*, *};
 
 // TODO: add tracing
@@ -30,12 +39,68 @@ use grpc::judger::{judger_client::*, *};
 /// Max score a health Upstream can reach
 const HEALTH_MAX_SCORE: isize = 100;
 
+/// delay before first retry of a failed upstream, doubled on each failure
+const BACKOFF_BASE: Duration = Duration::from_secs(1);
+const BACKOFF_MAX: Duration = Duration::from_secs(300);
+
+/// exponential backoff of upstreams that fail to connect, keyed by uri
+#[derive(Default)]
+pub struct Backoff(HashMap<String, (Instant, u32)>);
+
+impl Backoff {
+    /// record a failure and push back the deadline of next retry
+    pub fn fail(&mut self, uri: &str) {
+        let failures = self.0.get(uri).map_or(0, |x| x.1).saturating_add(1);
+        let delay = BACKOFF_BASE
+            .saturating_mul(1 << failures.min(16))
+            .min(BACKOFF_MAX);
+        self.0
+            .insert(uri.to_string(), (Instant::now() + delay, failures));
+    }
+    pub fn succeed(&mut self, uri: &str) {
+        self.0.remove(uri);
+    }
+    /// whether the uri can be connected now
+    pub fn ready(&self, uri: &str) -> bool {
+        self.0
+            .get(uri)
+            .map_or(true, |(deadline, _)| *deadline <= Instant::now())
+    }
+    /// time until the earliest retry, if any
+    pub fn next(&self) -> Option<Duration> {
+        self.0
+            .values()
+            .map(|(deadline, _)| deadline.saturating_duration_since(Instant::now()))
+            .min()
+    }
+    /// forget uris no longer provided by upstream source
+    pub fn retain(&mut self, mut f: impl FnMut(&str) -> bool) {
+        self.0.retain(|uri, _| f(uri));
+    }
+}
+
 /// Judge Client intercepted by BasicAuthInterceptor
 type AuthJudgerClient = JudgerClient<
     service::interceptor::InterceptedService<transport::Channel, BasicAuthInterceptor>,
 >;
 
+/// Inject trace context into metadata of grpc request
+struct MetadataInjector<'a>(&'a mut MetadataMap);
+
+impl<'a> Injector for MetadataInjector<'a> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(key), Ok(value)) = (
+            metadata::MetadataKey::from_bytes(key.as_bytes()),
+            value.parse(),
+        ) {
+            self.0.insert(key, value);
+        }
+    }
+}
+
 /// tower interceptor for Basic Auth
+///
+/// It also propagates trace context of current span to judger
 pub struct BasicAuthInterceptor {
     // Some if secret is set
     secret: Option<String>,
@@ -43,14 +108,15 @@ pub struct BasicAuthInterceptor {
 
 impl Interceptor for BasicAuthInterceptor {
     fn call(&mut self, mut req: tonic::Request<()>) -> Result<tonic::Request<()>, Status> {
-        match &self.secret {
-            Some(secret) => {
-                let token = secret.parse().unwrap();
-                req.metadata_mut().insert("Authorization", token);
-                Ok(req)
-            }
-            None => Ok(req),
+        let cx = Span::current().context();
+        opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&cx, &mut MetadataInjector(req.metadata_mut()))
+        });
+        if let Some(secret) = &self.secret {
+            let token = secret.parse().unwrap();
+            req.metadata_mut().insert("Authorization", token);
         }
+        Ok(req)
     }
 }
 
@@ -59,6 +125,7 @@ impl Interceptor for BasicAuthInterceptor {
 pub struct ConnectionDetail {
     pub uri: String,
     pub secret: Option<String>,
+    pub tls: Option<config::JudgerTls>,
     // TODO: reuse logic shouldn't be bound with connection creation logic
     pub reuse: bool,
 }
@@ -66,10 +133,11 @@ pub struct ConnectionDetail {
 impl ConnectionDetail {
     /// create a new connection
     async fn connect(&self) -> Result<AuthJudgerClient, Error> {
-        let channel = transport::Channel::from_shared(self.uri.clone())
-            .unwrap()
-            .connect()
-            .await?;
+        let mut endpoint = transport::Channel::from_shared(self.uri.clone()).unwrap();
+        if let Some(tls) = &self.tls {
+            endpoint = endpoint.tls_config(tls.load().await?)?;
+        }
+        let channel = endpoint.connect().await?;
 
         let interceptor = BasicAuthInterceptor {
             secret: self.secret.as_ref().map(|x| ["basic ", x].concat()),
@@ -141,7 +209,23 @@ async fn discover<I: Routable + Send>(
                     None => break,
                 };
                 let uri = detail.uri.clone();
-                let (upstream, langs) = Upstream::new(detail).in_current_span().await?;
+                let (upstream, langs) = match Upstream::new(detail).in_current_span().await {
+                    Ok(x) => x,
+                    Err(err) if I::REQUIRED => {
+                        tracing::error!(uri = %uri, "fail to connect upstream: {}", err);
+                        return Err(err);
+                    }
+                    Err(err) => {
+                        tracing::warn!(uri = %uri, "fail to connect upstream: {}", err);
+                        instance.connect_fail(&uri);
+                        continue;
+                    }
+                };
+                instance.connect_success(&uri);
+                if let Some(old) = router.upstreams.insert(uri.clone(), upstream.clone()) {
+                    old.close();
+                    router.prune_langs();
+                }
                 let _ = debug_span!(parent: parent.clone(), "connected", uri = uri).entered();
                 for (uuid, lang) in langs.into_iter() {
                     router.langs.insert(lang);
@@ -158,6 +242,18 @@ async fn discover<I: Routable + Send>(
                     }
                 }
             }
+            RouteStatus::Disconnect(uri) => {
+                let router = match router.upgrade() {
+                    Some(x) => x,
+                    None => break,
+                };
+                if let Some((_, upstream)) = router.upstreams.remove(&uri) {
+                    let _ =
+                        debug_span!(parent: parent.clone(), "disconnected", uri = %uri).entered();
+                    upstream.close();
+                    router.prune_langs();
+                }
+            }
             RouteStatus::Wait(dur) => tokio::time::sleep(dur).in_current_span().await,
             _ => break,
         }
@@ -171,16 +267,40 @@ async fn discover<I: Routable + Send>(
 /// and get judger client correspond to the chosen languages
 pub struct Router {
     routing_table: DashMap<Uuid, SegQueue<Arc<Upstream>>>,
+    /// every discovered upstream, keyed by uri
+    upstreams: DashMap<String, Arc<Upstream>>,
     pub langs: DashSet<LangInfo>,
+    _health: ObservableGauge<i64>,
+}
+
+/// observe health score of every upstream
+fn health_gauge(router: Weak<Router>) -> ObservableGauge<i64> {
+    meter()
+        .i64_observable_gauge("judger.upstream.health")
+        .with_description("health score of judger upstream")
+        .with_unit(Unit::new("score"))
+        .with_callback(move |observer| {
+            if let Some(router) = router.upgrade() {
+                for upstream in router.upstreams.iter() {
+                    observer.observe(
+                        upstream.healthy.load(Ordering::Relaxed) as i64,
+                        &[KeyValue::new("uri", upstream.key().clone())],
+                    );
+                }
+            }
+        })
+        .init()
 }
 
 impl Router {
     // skip because config contain basic auth secret
     #[instrument(name = "router_construct", level = "debug", skip_all)]
     pub fn new(config: Vec<JudgeConfig>) -> Result<Arc<Self>, Error> {
-        let self_ = Arc::new(Self {
+        let self_ = Arc::new_cyclic(|weak| Self {
             routing_table: DashMap::default(),
+            upstreams: DashMap::default(),
             langs: DashSet::default(),
+            _health: health_gauge(weak.clone()),
         });
         for config in config.into_iter() {
             match config.judger_type {
@@ -196,6 +316,12 @@ impl Router {
                         Arc::downgrade(&self_),
                     ));
                 }
+                config::JudgerType::Registry => {
+                    tokio::spawn(discover::<registry::RegistryRouter>(
+                        config,
+                        Arc::downgrade(&self_),
+                    ));
+                }
                 config::JudgerType::LoadBalanced => {
                     tokio::spawn(discover::<direct::StaticRouter<false>>(
                         config,
@@ -225,13 +351,26 @@ impl Router {
         // self.routing_table.remove(lang);
         Err(Error::BadArgument("lang"))
     }
+    /// remove languages no longer provided by any upstream
+    fn prune_langs(&self) {
+        let provided: HashSet<LangInfo> = self
+            .upstreams
+            .iter()
+            .flat_map(|x| x.langs.clone())
+            .collect();
+        self.langs.retain(|x| provided.contains(x));
+    }
 }
 
 // abstraction for pipelining
 pub struct Upstream {
     healthy: AtomicIsize,
+    /// removed by upstream source, never route to it again
+    closed: AtomicBool,
     clients: SegQueue<AuthJudgerClient>,
     connection: ConnectionDetail,
+    /// languages provided by the upstream
+    langs: Vec<LangInfo>,
 }
 
 impl Upstream {
@@ -260,15 +399,21 @@ impl Upstream {
         Ok((
             Arc::new(Self {
                 healthy: AtomicIsize::new(HEALTH_MAX_SCORE),
+                closed: AtomicBool::new(false),
                 clients,
                 connection: detail,
+                langs: result.iter().map(|(_, x)| x.clone()).collect(),
             }),
             result,
         ))
     }
     /// check if it's healthy
     fn is_healthy(&self) -> bool {
-        self.healthy.load(Ordering::Acquire) > 0
+        !self.closed.load(Ordering::Acquire) && self.healthy.load(Ordering::Acquire) > 0
+    }
+    /// mark as removed, it will be dropped from routing table lazily
+    fn close(&self) {
+        self.closed.store(true, Ordering::Release);
     }
     /// get new upstream
     async fn get(self: Arc<Self>) -> Result<ConnGuard, Error> {
@@ -289,6 +434,8 @@ impl Upstream {
 pub enum RouteStatus {
     /// discover new connection
     NewConnection(ConnectionDetail),
+    /// upstream with the uri is gone
+    Disconnect(String),
     /// wait for duration and apply next source discovery
     Wait(Duration),
     /// No new upstream
@@ -303,10 +450,17 @@ pub trait Routable
 where
     Self: Sized,
 {
+    /// whether failure to connect stop discovery instead of being retried later,
+    /// set for upstreams configured explicitly
+    const REQUIRED: bool = false;
     // return new connection when available, will immediately retry true is returned
     async fn route(&mut self) -> Result<RouteStatus, Error>;
     /// create from config
     fn new(config: JudgeConfig) -> Result<Self, Error>;
+    /// called when connection emitted by [`Routable::route`] fail
+    fn connect_fail(&mut self, _uri: &str) {}
+    /// called when connection emitted by [`Routable::route`] succeed
+    fn connect_success(&mut self, _uri: &str) {}
 }
 
 /// wrapper for Routable(Error handling)
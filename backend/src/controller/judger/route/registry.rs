@@ -0,0 +1,146 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+use super::{Backoff, ConnectionDetail, Error, Routable, RouteStatus};
+use crate::config::{Judger, JudgerTls};
+
+/// interval between two fetches of registry
+const REFRESH: Duration = Duration::from_secs(10);
+
+/// judger listed in registry
+#[derive(Deserialize, Clone, PartialEq, Eq)]
+struct Entry {
+    uri: String,
+    /// fallback to secret of the config entry if not set
+    #[serde(default)]
+    secret: Option<String>,
+}
+
+/// content of registry
+///
+/// A file registry is in toml, a http registry should response with json.
+///
+/// ```toml
+/// [[judger]]
+/// uri = "http://127.0.0.1:8080"
+/// secret = "secret"
+/// ```
+#[derive(Deserialize)]
+struct Registry {
+    #[serde(default)]
+    judger: Vec<Entry>,
+}
+
+/// Upstream source for a file or http service registry
+///
+/// Upstream is added or removed as the registry changes
+pub struct RegistryRouter {
+    source: String,
+    secret: Option<String>,
+    tls: Option<JudgerTls>,
+    /// judgers that have been emitted, keyed by uri
+    known: HashMap<String, Entry>,
+    backoff: Backoff,
+    pending: VecDeque<RouteStatus>,
+}
+
+impl RegistryRouter {
+    async fn fetch(&self) -> Result<Registry, String> {
+        if self.source.starts_with("http://") || self.source.starts_with("https://") {
+            reqwest::get(&self.source)
+                .await
+                .map_err(|x| x.to_string())?
+                .json()
+                .await
+                .map_err(|x| x.to_string())
+        } else {
+            let content = tokio::fs::read_to_string(&self.source)
+                .await
+                .map_err(|x| x.to_string())?;
+            toml::from_str(&content).map_err(|x| x.to_string())
+        }
+    }
+    fn connect(&self, entry: &Entry) -> RouteStatus {
+        RouteStatus::NewConnection(ConnectionDetail {
+            uri: entry.uri.clone(),
+            secret: entry.secret.clone().or_else(|| self.secret.clone()),
+            tls: self.tls.clone(),
+            reuse: true,
+        })
+    }
+    /// queue the changes between known judgers and registry
+    fn update(&mut self, registry: Registry) {
+        let entries: HashMap<String, Entry> = registry
+            .judger
+            .into_iter()
+            .map(|x| (x.uri.clone(), x))
+            .collect();
+
+        let removed: Vec<String> = self
+            .known
+            .keys()
+            .filter(|uri| !entries.contains_key(*uri))
+            .cloned()
+            .collect();
+        self.backoff.retain(|uri| entries.contains_key(uri));
+        for uri in removed {
+            self.known.remove(&uri);
+            self.pending.push_back(RouteStatus::Disconnect(uri));
+        }
+
+        for (uri, entry) in entries {
+            match self.known.get(&uri) {
+                Some(x) if *x == entry => continue,
+                // secret changed, reconnect
+                Some(_) => self.pending.push_back(RouteStatus::Disconnect(uri.clone())),
+                None if !self.backoff.ready(&uri) => continue,
+                None => {}
+            }
+            self.pending.push_back(self.connect(&entry));
+            self.known.insert(uri, entry);
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Routable for RegistryRouter {
+    async fn route(&mut self) -> Result<RouteStatus, Error> {
+        if let Some(status) = self.pending.pop_front() {
+            return Ok(status);
+        }
+        match self.fetch().await {
+            Ok(registry) => self.update(registry),
+            // registry may be temporarily unavailable, keep current upstreams
+            Err(err) => tracing::warn!(source = %self.source, "fail to fetch registry: {}", err),
+        }
+        Ok(self
+            .pending
+            .pop_front()
+            .unwrap_or(RouteStatus::Wait(REFRESH)))
+    }
+
+    fn new(config: Judger) -> Result<Self, Error> {
+        Ok(Self {
+            source: config.name,
+            secret: config.secret,
+            tls: config.tls,
+            known: HashMap::new(),
+            backoff: Backoff::default(),
+            pending: VecDeque::new(),
+        })
+    }
+
+    fn connect_fail(&mut self, uri: &str) {
+        // retry on fetch after backoff
+        self.known.remove(uri);
+        self.backoff.fail(uri);
+    }
+
+    fn connect_success(&mut self, uri: &str) {
+        self.backoff.succeed(uri);
+    }
+}
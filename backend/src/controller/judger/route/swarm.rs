@@ -1,6 +1,6 @@
 use std::{collections::HashSet, net::IpAddr, time::Duration};
 
-use super::{ConnectionDetail, Error};
+use super::{Backoff, ConnectionDetail, Error};
 use crate::config;
 use hickory_resolver::TokioAsyncResolver;
 
@@ -10,14 +10,20 @@ use super::{Routable, RouteStatus};
 pub struct SwarmRouter {
     dns: String,
     secret: Option<String>,
+    tls: Option<config::JudgerTls>,
     address: HashSet<IpAddr>,
+    backoff: Backoff,
     resolver: TokioAsyncResolver,
 }
 
-fn to_uri(ip: &IpAddr) -> String {
+fn to_uri(ip: &IpAddr, tls: bool) -> String {
+    let scheme = match tls {
+        true => "https",
+        false => "http",
+    };
     match ip {
-        IpAddr::V4(ip) => format!("http://{}", ip),
-        IpAddr::V6(ip) => format!("http://[{}]", ip),
+        IpAddr::V4(ip) => format!("{}://{}", scheme, ip),
+        IpAddr::V6(ip) => format!("{}://[{}]", scheme, ip),
     }
 }
 
@@ -25,31 +31,67 @@ fn to_uri(ip: &IpAddr) -> String {
 impl Routable for SwarmRouter {
     async fn route(&mut self) -> Result<RouteStatus, Error> {
         let result = self.resolver.lookup_ip(self.dns.as_str()).await?;
-        let ips = result.as_lookup().records().iter().filter_map(|x| {
-            let data = x.data()?;
-            data.ip_addr()
-        });
+        let ips: HashSet<IpAddr> = result
+            .as_lookup()
+            .records()
+            .iter()
+            .filter_map(|x| {
+                let data = x.data()?;
+                data.ip_addr()
+            })
+            .collect();
+
+        if let Some(ip) = self.address.difference(&ips).next().cloned() {
+            self.address.remove(&ip);
+            return Ok(RouteStatus::Disconnect(to_uri(&ip, self.tls.is_some())));
+        }
+
+        let uris: HashSet<String> = ips
+            .iter()
+            .map(|ip| to_uri(ip, self.tls.is_some()))
+            .collect();
+        self.backoff.retain(|uri| uris.contains(uri));
 
         for ip in ips {
             if !self.address.contains(&ip) {
-                let uri = to_uri(&ip);
+                let uri = to_uri(&ip, self.tls.is_some());
+                if !self.backoff.ready(&uri) {
+                    continue;
+                }
                 self.address.insert(ip);
                 return Ok(RouteStatus::NewConnection(ConnectionDetail {
                     uri,
                     secret: self.secret.clone(),
+                    tls: self.tls.clone(),
                     reuse: true,
                 }));
             }
         }
-        return Ok(RouteStatus::Wait(Duration::from_secs(30)));
+        let wait = Duration::from_secs(30);
+        Ok(RouteStatus::Wait(
+            self.backoff.next().map_or(wait, |x| x.min(wait)),
+        ))
     }
 
     fn new(config: config::Judger) -> Result<Self, Error> {
         Ok(Self {
             dns: config.name,
             secret: config.secret,
+            tls: config.tls,
             address: HashSet::new(),
+            backoff: Backoff::default(),
             resolver: TokioAsyncResolver::tokio_from_system_conf()?,
         })
     }
+
+    fn connect_fail(&mut self, uri: &str) {
+        // retry on lookup after backoff
+        self.address
+            .retain(|ip| to_uri(ip, self.tls.is_some()) != uri);
+        self.backoff.fail(uri);
+    }
+
+    fn connect_success(&mut self, uri: &str) {
+        self.backoff.succeed(uri);
+    }
 }
@@ -1,5 +1,3 @@
-use std::cmp;
-
 use super::{submit, user_contest};
 use crate::{
     entity::{contest, problem, user},
@@ -7,8 +5,8 @@ use crate::{
 };
 use chrono::Local;
 use sea_orm::{
-    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel,
-    ModelTrait, QueryFilter, QueryOrder, TransactionTrait,
+    sea_query::Expr, ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait,
+    IntoActiveModel, ModelTrait, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
 };
 use tracing::instrument;
 
@@ -122,40 +120,81 @@ impl ScoreUpload {
 
         let contest_id = self.problem.contest_id.unwrap();
 
-        let (contest, linker) = contest::Entity::find_by_id(contest_id)
-            .find_also_related(user_contest::Entity)
+        let contest = contest::Entity::find_by_id(contest_id)
             .one(&txn)
             .await
             .map_err(Into::<Error>::into)?
             .ok_or(Error::NotInDB)?;
 
-        let now = Local::now().naive_local();
-
-        if let Some(end) = contest.end {
-            if end < now {
-                tracing::trace!(reason = "contest ended", "score_contest");
-                return Ok(());
-            }
-        }
-
         if contest.host == self.user_id {
             tracing::trace!(reason = "owner score bypass", "score_contest");
             return Ok(());
         }
 
-        let mut linker = linker
-            .ok_or(Error::Unreachable("user_contest should exist"))?
-            .into_active_model();
+        let Some(linker) = user_contest::Entity::find()
+            .filter(user_contest::Column::ContestId.eq(contest_id))
+            .filter(user_contest::Column::UserId.eq(self.user_id))
+            .one(&txn)
+            .await
+            .map_err(Into::<Error>::into)?
+        else {
+            tracing::trace!(reason = "not participant", "score_contest");
+            return Ok(());
+        };
 
-        let mut score = linker.score.unwrap();
+        if linker.banned {
+            tracing::trace!(reason = "banned participant", "score_contest");
+            return Ok(());
+        }
 
-        let submit = self
+        // virtual participation replays the contest from its own start
+        let begin = match linker.virtual_begin {
+            Some(virtual_begin) => {
+                // contest without begin starts at creation, the same as scoreboard
+                let begin = contest.begin.unwrap_or(contest.create_at);
+                let end = contest.end.map(|end| virtual_begin + (end - begin));
+                if self.submit.upload_at < virtual_begin
+                    || end.is_some_and(|x| x < self.submit.upload_at)
+                {
+                    tracing::trace!(reason = "out of virtual window", "score_contest");
+                    return Ok(());
+                }
+                Some(virtual_begin)
+            }
+            None => {
+                let now = Local::now().naive_local();
+                if contest.end.is_some_and(|x| x < now) {
+                    tracing::trace!(reason = "contest ended", "score_contest");
+                    return Ok(());
+                }
+                contest.begin
+            }
+        };
+
+        // members of a team share the score
+        let members: Vec<i32> = match linker.team_id {
+            Some(team_id) => {
+                user_contest::Entity::find()
+                    .filter(user_contest::Column::ContestId.eq(contest_id))
+                    .filter(user_contest::Column::TeamId.eq(team_id))
+                    .select_only()
+                    .column(user_contest::Column::UserId)
+                    .into_tuple()
+                    .all(&txn)
+                    .await?
+            }
+            None => vec![self.user_id],
+        };
+
+        let mut query = self
             .problem
             .find_related(submit::Entity)
-            .filter(submit::Column::UserId.eq(self.user_id))
-            .order_by_desc(submit::Column::Score)
-            .one(&txn)
-            .await?;
+            .filter(submit::Column::UserId.is_in(members.clone()))
+            .filter(submit::Column::Id.ne(self.submit.id));
+        if let Some(begin) = begin {
+            query = query.filter(submit::Column::UploadAt.gte(begin));
+        }
+        let submit = query.order_by_desc(submit::Column::Score).one(&txn).await?;
 
         let original_score = submit.map(|x| x.score).unwrap_or_default();
 
@@ -164,11 +203,16 @@ impl ScoreUpload {
             return Ok(());
         }
 
-        score = score.saturating_add(cmp::max(self.submit.score, original_score));
-        score = score.saturating_sub(original_score);
-
-        linker.score = ActiveValue::Set(score);
-        linker.update(&txn).await.map_err(Into::<Error>::into)?;
+        user_contest::Entity::update_many()
+            .col_expr(
+                user_contest::Column::Score,
+                Expr::col(user_contest::Column::Score).add(self.submit.score - original_score),
+            )
+            .filter(user_contest::Column::ContestId.eq(contest_id))
+            .filter(user_contest::Column::UserId.is_in(members))
+            .exec(&txn)
+            .await
+            .map_err(Into::<Error>::into)?;
 
         txn.commit().await.map_err(Into::<Error>::into)
     }
@@ -3,8 +3,10 @@
 //! controller are stateful, independent conponment and should
 //! include intergated test
 
+pub mod clarification;
 pub mod crypto;
 pub mod imgur;
 pub mod judger;
 pub mod rate_limit;
 pub mod token;
+pub mod upload;
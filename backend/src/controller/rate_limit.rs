@@ -60,7 +60,13 @@ impl Bucket {
     }
     pub fn cost(&self, cost: NonZeroU32) -> Result<(), Error> {
         match self.expect_dur(cost) {
-            true => Err(Error::RateLimit("")),
+            true => {
+                info!(
+                    monotonic_counter.rate_limit.reject = 1,
+                    bucket = self.get_name()
+                );
+                Err(Error::RateLimit(""))
+            }
             false => Ok(()),
         }
     }
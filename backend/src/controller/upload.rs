@@ -0,0 +1,125 @@
+//! chunked upload
+//!
+//! gRPC-web doesn't support client streaming, so large payload is uploaded
+//! by unary calls keyed by upload id, and consumed by another call later.
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use tonic::Status;
+use uuid::Uuid;
+
+/// upload untouched for this long is dropped
+const EXPIRE: Duration = Duration::from_secs(600);
+/// max number of unfinished uploads of a user
+const MAX_UPLOAD_PER_USER: usize = 4;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("upload not found")]
+    NotFound,
+    #[error("upload too large")]
+    TooLarge,
+    #[error("too many unfinished uploads")]
+    TooMany,
+}
+
+impl From<Error> for Status {
+    fn from(value: Error) -> Self {
+        match value {
+            Error::NotFound => Status::not_found("upload not found"),
+            Error::TooLarge => Status::invalid_argument("upload too large"),
+            Error::TooMany => Status::resource_exhausted("too many unfinished uploads"),
+        }
+    }
+}
+
+struct Upload {
+    data: Vec<u8>,
+    touch: Instant,
+}
+
+/// uploads are grouped by user, so counting and inserting uploads of a
+/// user happen under the same entry lock
+#[derive(Default)]
+pub struct UploadController {
+    uploads: DashMap<i32, HashMap<Uuid, Upload>>,
+}
+
+impl UploadController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// drop uploads untouched for [`EXPIRE`]
+    fn expire(&self) {
+        self.uploads.retain(|_, x| {
+            x.retain(|_, x| x.touch.elapsed() < EXPIRE);
+            !x.is_empty()
+        });
+    }
+    /// append chunk to the upload, a new upload is started if `id` is none
+    ///
+    /// return id of the upload and bytes received so far, the upload
+    /// is dropped if it exceeds `limit`
+    pub fn append(
+        &self,
+        user_id: i32,
+        id: Option<Uuid>,
+        chunk: Vec<u8>,
+        limit: usize,
+    ) -> Result<(Uuid, u64), Error> {
+        self.expire();
+
+        let mut uploads = self.uploads.entry(user_id).or_default();
+        let id = match id {
+            Some(id) => id,
+            None => {
+                if uploads.len() >= MAX_UPLOAD_PER_USER {
+                    return Err(Error::TooMany);
+                }
+                let id = Uuid::new_v4();
+                uploads.insert(
+                    id,
+                    Upload {
+                        data: Vec::new(),
+                        touch: Instant::now(),
+                    },
+                );
+                id
+            }
+        };
+
+        let upload = uploads.get_mut(&id).ok_or(Error::NotFound)?;
+        if upload.data.len() + chunk.len() > limit {
+            uploads.remove(&id);
+            return Err(Error::TooLarge);
+        }
+        upload.data.extend_from_slice(&chunk);
+        upload.touch = Instant::now();
+        Ok((id, upload.data.len() as u64))
+    }
+    /// remove the upload and get its content
+    pub fn take(&self, user_id: i32, id: Uuid) -> Result<Vec<u8>, Error> {
+        self.expire();
+
+        self.uploads
+            .get_mut(&user_id)
+            .and_then(|mut x| x.remove(&id))
+            .map(|x| x.data)
+            .ok_or(Error::NotFound)
+    }
+    /// put content of a taken upload back, so it can be taken again
+    ///
+    /// used when the content is rejected and client may retry
+    pub fn restore(&self, user_id: i32, id: Uuid, data: Vec<u8>) {
+        self.uploads.entry(user_id).or_default().insert(
+            id,
+            Upload {
+                data,
+                touch: Instant::now(),
+            },
+        );
+    }
+}
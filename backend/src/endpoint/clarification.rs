@@ -0,0 +1,231 @@
+use super::*;
+use chrono::Local;
+use tokio_stream::{wrappers::BroadcastStream, StreamExt};
+
+use grpc::backend::clarification_server::*;
+
+use crate::entity::{
+    clarification::{Paginator, *},
+    contest, problem, user_contest,
+};
+
+impl From<Model> for ClarificationInfo {
+    fn from(value: Model) -> Self {
+        ClarificationInfo {
+            id: value.id,
+            contest_id: value.contest_id,
+            problem_id: value.problem_id,
+            user_id: value.user_id,
+            question: value.question,
+            answer: value.answer,
+            public: value.public,
+            create_at: into_prost(value.create_at),
+            answer_at: value.answer_at.map(into_prost),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl Clarification for ArcServer {
+    type WatchStream = TonicStream<ClarificationInfo>;
+
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Clarification/create",
+        err(level = "debug", Display)
+    )]
+    async fn create(
+        &self,
+        req: Request<CreateClarificationRequest>,
+    ) -> Result<Response<Id>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        let (user_id, _) = auth.assume_login()?;
+
+        req.bound_check()?;
+
+        req.get_or_insert(|req| async move {
+            // only participant can ask
+            user_contest::Entity::find()
+                .filter(user_contest::Column::ContestId.eq(req.contest_id))
+                .filter(user_contest::Column::UserId.eq(user_id))
+                .filter(user_contest::Column::Banned.eq(false))
+                .one(self.db.deref())
+                .instrument(info_span!("fetch_pivot").or_current())
+                .await
+                .map_err(Into::<Error>::into)?
+                .ok_or(Error::NotInDB)?;
+
+            if let Some(problem_id) = req.problem_id {
+                problem::Entity::find_by_id(problem_id)
+                    .filter(problem::Column::ContestId.eq(req.contest_id))
+                    .one(self.db.deref())
+                    .instrument(info_span!("fetch_problem").or_current())
+                    .await
+                    .map_err(Into::<Error>::into)?
+                    .ok_or(Error::NotInDB)?;
+            }
+
+            let mut model: ActiveModel = Default::default();
+            model.user_id = ActiveValue::Set(user_id);
+            model.problem_id = ActiveValue::Set(req.problem_id);
+
+            fill_active_model!(model, req, contest_id, question);
+
+            let model = model
+                .insert(self.db.deref())
+                .instrument(info_span!("save").or_current())
+                .await
+                .map_err(Into::<Error>::into)?;
+
+            let id = model.id;
+            self.clarification.publish(model);
+            info!(counter.clarification = 1, id = id);
+
+            Ok(id.into())
+        })
+        .await
+        .with_grpc()
+        .into()
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Clarification/answer",
+        err(level = "debug", Display)
+    )]
+    async fn answer(
+        &self,
+        req: Request<AnswerClarificationRequest>,
+    ) -> Result<Response<()>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+
+        req.bound_check()?;
+
+        req.get_or_insert(|req| async move {
+            let mut model = Entity::find_by_id(req.id)
+                .with_auth(&auth)
+                .write()?
+                .one(self.db.deref())
+                .instrument(info_span!("fetch").or_current())
+                .await
+                .map_err(Into::<Error>::into)?
+                .ok_or(Error::NotInDB)?
+                .into_active_model();
+
+            model.answer = ActiveValue::Set(Some(req.answer));
+            model.public = ActiveValue::Set(req.public);
+            model.answer_at = ActiveValue::Set(Some(Local::now().naive_local()));
+
+            let model = model
+                .update(self.db.deref())
+                .instrument(info_span!("update").or_current())
+                .await
+                .map_err(Into::<Error>::into)?;
+
+            debug!(
+                id = model.id,
+                public = model.public,
+                "clarification_answered"
+            );
+            self.clarification.publish(model);
+
+            Ok(())
+        })
+        .await
+        .with_grpc()
+        .into()
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Clarification/remove",
+        err(level = "debug", Display)
+    )]
+    async fn remove(&self, req: Request<RemoveRequest>) -> Result<Response<()>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+
+        req.get_or_insert(|req| async move {
+            let result = Entity::delete_by_id(req.id)
+                .with_auth(&auth)
+                .write()?
+                .exec(self.db.deref())
+                .instrument(info_span!("remove").or_current())
+                .await
+                .map_err(Into::<Error>::into)?;
+
+            if result.rows_affected == 0 {
+                Err(Error::NotInDB)
+            } else {
+                info!(counter.clarification = -1, id = req.id);
+                Ok(())
+            }
+        })
+        .await
+        .with_grpc()
+        .into()
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Clarification/list",
+        err(level = "debug", Display)
+    )]
+    async fn list(
+        &self,
+        req: Request<ListClarificationRequest>,
+    ) -> Result<Response<ListClarificationResponse>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+
+        req.bound_check()?;
+
+        let paginator = match req.request.ok_or(Error::NotInPayload("request"))? {
+            list_clarification_request::Request::Create(create) => {
+                let start_from_end = create.order == Order::Descend as i32;
+                Paginator::new(create.contest_id, start_from_end)
+            }
+            list_clarification_request::Request::Paginator(x) => self.crypto.decode(x)?,
+        };
+        let mut paginator = paginator.with_auth(&auth).with_db(&self.db);
+
+        let list = paginator
+            .fetch(req.size, req.offset)
+            .in_current_span()
+            .await?;
+        let remain = paginator.remain().in_current_span().await?;
+
+        let paginator = paginator.into_inner();
+
+        Ok(Response::new(ListClarificationResponse {
+            list: list.into_iter().map(Into::into).collect(),
+            paginator: self.crypto.encode(paginator)?,
+            remain,
+        }))
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Clarification/watch",
+        err(level = "debug", Display)
+    )]
+    async fn watch(&self, req: Request<Id>) -> Result<Response<Self::WatchStream>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        let (user_id, perm) = auth.assume_login()?;
+
+        let contest: contest::IdModel =
+            contest::Entity::related_read_by_id(&auth, req.id, &self.db)
+                .in_current_span()
+                .await?;
+        let host = perm >= RoleLv::Admin || contest.host == user_id;
+
+        let rx = self.clarification.subscribe(contest.id);
+        // participant is only notified of answers, host of everything
+        let stream = BroadcastStream::new(rx).filter_map(move |model| {
+            let model = model.ok()?;
+            (host || (model.answer.is_some() && model.visible_to(Some(user_id))))
+                .then(|| Ok(ClarificationInfo::from(model)))
+        });
+
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
@@ -1,13 +1,21 @@
 use super::*;
 use crate::entity::{
     contest::{Paginator, *},
-    problem, *,
+    problem, scoreboard, *,
 };
+use crate::{controller::judger, util::auth::Auth};
 use chrono::Local;
 use sea_orm::sea_query::Expr;
+use std::{collections::HashMap, sync::Arc};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    StreamExt,
+};
 
 use grpc::backend::contest_server::*;
-use grpc::backend::update_contest_request::info::{End, Password};
+use grpc::backend::update_contest_request::info::{
+    End, Freeze, Password, RegisterBegin, RegisterEnd,
+};
 
 impl<'a> From<WithAuth<'a, Model>> for ContestFullInfo {
     fn from(value: WithAuth<'a, Model>) -> Self {
@@ -18,6 +26,7 @@ impl<'a> From<WithAuth<'a, Model>> for ContestFullInfo {
             content: model.content,
             host: model.host,
             writable,
+            rule: model.rule,
         }
     }
 }
@@ -33,6 +42,37 @@ impl From<Model> for ContestInfo {
             end: value.end.map(into_prost),
             need_password: value.password.is_some(),
             public: value.public,
+            freeze_at: value.freeze_at.map(into_prost),
+            register_begin: value.register_begin.map(into_prost),
+            register_end: value.register_end.map(into_prost),
+        }
+    }
+}
+
+impl From<scoreboard::Cell> for ScoreboardCell {
+    fn from(value: scoreboard::Cell) -> Self {
+        ScoreboardCell {
+            problem_id: value.problem_id,
+            attempt: value.attempt,
+            accept_at: value.accept_at.map(|x| x.max(0) as u64),
+            score: value.score,
+            pending: value.pending,
+        }
+    }
+}
+
+impl From<scoreboard::Row> for ScoreboardRow {
+    fn from(value: scoreboard::Row) -> Self {
+        ScoreboardRow {
+            rank: value.rank,
+            user_id: value.user_id,
+            username: value.username,
+            solved: value.solved,
+            penalty: value.penalty.max(0) as u64,
+            score: value.score,
+            cells: value.cells.into_iter().map(Into::into).collect(),
+            team_id: value.team_id,
+            virtual_begin: value.virtual_begin.map(into_prost),
         }
     }
 }
@@ -46,12 +86,85 @@ impl From<PartialModel> for ContestInfo {
             end: value.end.map(into_prost),
             need_password: value.password.is_some(),
             public: value.public,
+            freeze_at: value.freeze_at.map(into_prost),
+            register_begin: value.register_begin.map(into_prost),
+            register_end: value.register_end.map(into_prost),
         }
     }
 }
 
+/// max number of members in a team, banned members are not counted
+const MAX_TEAM_SIZE: u64 = 3;
+
+/// subscriber of `Contest.Watch`
+struct Watcher {
+    contest_id: i32,
+    auth: Auth,
+    db: Arc<DatabaseConnection>,
+}
+
+impl Watcher {
+    async fn resync(&self) -> Result<ContestEvent, Status> {
+        let list = scoreboard::board(self.contest_id, false, &self.auth, &self.db).await?;
+        Ok(ContestEvent {
+            event: Some(contest_event::Event::Resync(contest_event::Resync {
+                list: list.into_iter().map(Into::into).collect(),
+            })),
+        })
+    }
+    /// hide result if scoreboard is frozen for subscriber
+    async fn submission(&self, event: judger::ContestEvent) -> Result<ContestEvent, Status> {
+        // contest may be updated(like unfrozen) after subscribing
+        let contest = event
+            .contest
+            .get_or_try_init(|| async {
+                Entity::find_by_id(self.contest_id)
+                    .one(self.db.deref())
+                    .await?
+                    .ok_or(Error::NotInDB)
+            })
+            .await?;
+        let freeze = contest.freeze_for(&self.auth);
+        let hidden = Some(event.user_id) != self.auth.user_id()
+            && freeze.is_some_and(|x| event.upload_at >= x);
+        let state = event.status.filter(|_| !hidden);
+
+        let row = match state {
+            Some(_) => {
+                let board = event.board[freeze.is_some() as usize]
+                    .get_or_try_init(|| async {
+                        scoreboard::compute(contest, false, freeze, None, &self.db)
+                            .await
+                            .map(Arc::new)
+                    })
+                    .await?;
+                board
+                    .iter()
+                    .find(|x| x.members.contains(&event.user_id))
+                    .cloned()
+                    .map(Into::into)
+            }
+            None => None,
+        };
+
+        Ok(ContestEvent {
+            event: Some(contest_event::Event::Submission(
+                contest_event::Submission {
+                    submit_id: event.submit_id,
+                    user_id: event.user_id,
+                    problem_id: event.problem_id,
+                    state: state.map(|x| Into::<StateCode>::into(x) as i32),
+                    row,
+                },
+            )),
+        })
+    }
+}
+
 #[async_trait]
 impl Contest for ArcServer {
+    type WatchStream = TonicStream<ContestEvent>;
+
     #[instrument(
         skip_all,
         level = "info",
@@ -141,6 +254,10 @@ impl Contest for ArcServer {
             }
 
             model.end = req.info.end.map(into_chrono).into_active_value();
+            model.rule = ActiveValue::Set(req.info.rule.unwrap_or_default());
+            model.freeze_at = req.info.freeze_at.map(into_chrono).into_active_value();
+            model.register_begin = req.info.register_begin.map(into_chrono).into_active_value();
+            model.register_end = req.info.register_end.map(into_chrono).into_active_value();
 
             let model = model
                 .save(self.db.deref())
@@ -200,7 +317,7 @@ impl Contest for ArcServer {
 
             let mut model = model.into_active_model();
 
-            fill_exist_active_model!(model, req.info, title, content, tags);
+            fill_exist_active_model!(model, req.info, title, content, tags, rule);
             match req.info.end {
                 Some(End::EndSet(x)) => {
                     model.end = ActiveValue::Set(Some(into_chrono(x)));
@@ -210,6 +327,33 @@ impl Contest for ArcServer {
                 }
                 _ => {}
             }
+            match req.info.freeze {
+                Some(Freeze::FreezeSet(x)) => {
+                    model.freeze_at = ActiveValue::Set(Some(into_chrono(x)));
+                }
+                Some(Freeze::FreezeUnset(_)) => {
+                    model.freeze_at = ActiveValue::Set(None);
+                }
+                _ => {}
+            }
+            match req.info.register_begin {
+                Some(RegisterBegin::RegisterBeginSet(x)) => {
+                    model.register_begin = ActiveValue::Set(Some(into_chrono(x)));
+                }
+                Some(RegisterBegin::RegisterBeginUnset(_)) => {
+                    model.register_begin = ActiveValue::Set(None);
+                }
+                _ => {}
+            }
+            match req.info.register_end {
+                Some(RegisterEnd::RegisterEndSet(x)) => {
+                    model.register_end = ActiveValue::Set(Some(into_chrono(x)));
+                }
+                Some(RegisterEnd::RegisterEndUnset(_)) => {
+                    model.register_end = ActiveValue::Set(None);
+                }
+                _ => {}
+            }
 
             model
                 .update(self.db.deref())
@@ -336,6 +480,177 @@ impl Contest for ArcServer {
         err(level = "debug", Display)
     )]
     async fn join(&self, req: Request<JoinContestRequest>) -> Result<Response<()>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        req.bound_check()?;
+        let (user_id, _) = auth.assume_login()?;
+
+        req.get_or_insert(|req| async move {
+            let model = Entity::find_by_id(req.id)
+                .with_auth(&auth)
+                .read()?
+                .one(self.db.deref())
+                .instrument(info_span!("fetch").or_current())
+                .await
+                .map_err(Into::<Error>::into)?
+                .ok_or(Error::NotInDB)?;
+
+            let txn = self.db.begin().await?;
+
+            let invite = match req.invite_code.as_ref() {
+                Some(code) => Some(
+                    contest_invite::Entity::find()
+                        .filter(contest_invite::Column::ContestId.eq(model.id))
+                        .filter(contest_invite::Column::Code.eq(code))
+                        .one(&txn)
+                        .instrument(info_span!("fetch_invite").or_current())
+                        .await?
+                        .ok_or(Error::PermissionDeny("invalid invite code"))?,
+                ),
+                None => None,
+            };
+
+            // invitation of host bypass password and registration window
+            if !invite.as_ref().is_some_and(|x| x.by_host) {
+                if let Some(tar) = &model.password {
+                    let password = req
+                        .password
+                        .as_ref()
+                        .ok_or(Error::NotInPayload("password"))?;
+                    if !self.crypto.hash_eq(password, tar) {
+                        return Err(Error::PermissionDeny("mismatched password"));
+                    }
+                }
+
+                if !model.register_open(Local::now().naive_local()) {
+                    return Err(Error::PermissionDeny("registration closed"));
+                }
+            }
+
+            let team_id = invite.as_ref().and_then(|x| x.team_id);
+            if let Some(team_id) = team_id {
+                // write to team row before counting, so concurrent joins of
+                // the same team hold the write lock in turn
+                let locked = team::Entity::update_many()
+                    .col_expr(team::Column::Id, Expr::col(team::Column::Id).into())
+                    .filter(team::Column::Id.eq(team_id))
+                    .exec(&txn)
+                    .instrument(info_span!("lock_team").or_current())
+                    .await
+                    .map_err(|_| Error::Retry)?;
+                if locked.rows_affected == 0 {
+                    return Err(Error::NotInDB);
+                }
+                let size = user_contest::Entity::find()
+                    .filter(user_contest::Column::TeamId.eq(team_id))
+                    .filter(user_contest::Column::Banned.eq(false))
+                    .filter(user_contest::Column::UserId.ne(user_id))
+                    .count(&txn)
+                    .instrument(info_span!("count_member").or_current())
+                    .await?;
+                if size >= MAX_TEAM_SIZE {
+                    return Err(Error::PermissionDeny("team is full"));
+                }
+            }
+            let pivot = user_contest::Entity::find()
+                .filter(user_contest::Column::ContestId.eq(model.id))
+                .filter(user_contest::Column::UserId.eq(user_id))
+                .one(&txn)
+                .instrument(info_span!("fetch_pivot").or_current())
+                .await?;
+
+            match pivot {
+                // participant without team can join a team by invitation
+                Some(pivot) if !pivot.banned && pivot.team_id.is_none() && team_id.is_some() => {
+                    let mut pivot = pivot.into_active_model();
+                    pivot.team_id = ActiveValue::Set(team_id);
+                    pivot
+                        .update(&txn)
+                        .instrument(info_span!("update_pviot").or_current())
+                        .await?;
+                }
+                Some(pivot) if pivot.banned => {
+                    return Err(Error::PermissionDeny("banned from contest"));
+                }
+                Some(_) => return Err(Error::AlreadyExist("participant")),
+                None => {
+                    user_contest::ActiveModel {
+                        user_id: ActiveValue::Set(user_id),
+                        contest_id: ActiveValue::Set(model.id),
+                        team_id: ActiveValue::Set(team_id),
+                        ..Default::default()
+                    }
+                    .save(&txn)
+                    .instrument(info_span!("insert_pviot").or_current())
+                    .await?;
+                }
+            }
+
+            if let Some(invite) = invite {
+                invite
+                    .delete(&txn)
+                    .instrument(info_span!("consume_invite").or_current())
+                    .await?;
+            }
+
+            txn.commit().await.map_err(|_| Error::Retry)?;
+
+            debug!(user_id = user_id, contest_id = req.id);
+            Ok(())
+        })
+        .await
+        .with_grpc()
+        .into()
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Contest/leave",
+        err(level = "debug", Display)
+    )]
+    async fn leave(&self, req: Request<Id>) -> Result<Response<()>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        let (user_id, _) = auth.assume_login()?;
+
+        let pivot = user_contest::Entity::find()
+            .filter(user_contest::Column::ContestId.eq(req.id))
+            .filter(user_contest::Column::UserId.eq(user_id))
+            .one(self.db.deref())
+            .instrument(info_span!("fetch_pivot").or_current())
+            .await
+            .map_err(Into::<Error>::into)?
+            .ok_or(Error::NotInDB)?;
+        // virtual participant is kept, so it can't restart the virtual contest
+        if pivot.virtual_begin.is_some() {
+            return Err(Error::PermissionDeny("virtual participant cannot leave").into());
+        }
+
+        // banned participant is kept, so it can't join again
+        let result = user_contest::Entity::delete_many()
+            .filter(user_contest::Column::ContestId.eq(req.id))
+            .filter(user_contest::Column::UserId.eq(user_id))
+            .filter(user_contest::Column::Banned.eq(false))
+            .filter(user_contest::Column::VirtualBegin.is_null())
+            .exec(self.db.deref())
+            .instrument(info_span!("remove_pivot").or_current())
+            .await
+            .map_err(Into::<Error>::into)?;
+
+        if result.rows_affected == 0 {
+            return Err(Error::NotInDB.into());
+        }
+        debug!(user_id = user_id, contest_id = req.id);
+        Ok(Response::new(()))
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Contest/start_virtual",
+        err(level = "debug", Display)
+    )]
+    async fn start_virtual(
+        &self,
+        req: Request<StartVirtualRequest>,
+    ) -> Result<Response<()>, Status> {
         let (auth, req) = self.rate_limit(req).in_current_span().await?;
         let (user_id, _) = auth.assume_login()?;
 
@@ -349,39 +664,388 @@ impl Contest for ArcServer {
                 .map_err(Into::<Error>::into)?
                 .ok_or(Error::NotInDB)?;
 
-            if let Some(tar) = model.password {
+            if let Some(tar) = &model.password {
                 let password = req
                     .password
                     .as_ref()
                     .ok_or(Error::NotInPayload("password"))?;
-                if !self.crypto.hash_eq(password, &tar) {
+                if !self.crypto.hash_eq(password, tar) {
                     return Err(Error::PermissionDeny("mismatched password"));
                 }
             }
 
-            if let Some(begin) = model.begin {
-                if begin > Local::now().naive_local() {
-                    return Err(Error::PermissionDeny("contest not begin"));
-                }
+            let now = Local::now().naive_local();
+            if !model.end.is_some_and(|x| x < now) {
+                return Err(Error::PermissionDeny("contest not ended"));
             }
 
-            let pivot = user_contest::ActiveModel {
+            let exist = user_contest::Entity::find()
+                .filter(user_contest::Column::ContestId.eq(model.id))
+                .filter(user_contest::Column::UserId.eq(user_id))
+                .count(self.db.deref())
+                .instrument(info_span!("fetch_pivot").or_current())
+                .await?;
+            if exist != 0 {
+                return Err(Error::AlreadyExist("participant"));
+            }
+
+            user_contest::ActiveModel {
                 user_id: ActiveValue::Set(user_id),
                 contest_id: ActiveValue::Set(model.id),
+                virtual_begin: ActiveValue::Set(Some(now)),
                 ..Default::default()
-            };
+            }
+            .save(self.db.deref())
+            .instrument(info_span!("insert_pviot").or_current())
+            .await?;
+
+            debug!(user_id = user_id, contest_id = req.id, "virtual");
+            Ok(())
+        })
+        .await
+        .with_grpc()
+        .into()
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Contest/create_team",
+        err(level = "debug", Display)
+    )]
+    async fn create_team(&self, req: Request<CreateTeamRequest>) -> Result<Response<Id>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        req.bound_check()?;
+        let (user_id, _) = auth.assume_login()?;
+
+        req.get_or_insert(|req| async move {
+            let txn = self.db.begin().await?;
+
+            let pivot = user_contest::Entity::find()
+                .filter(user_contest::Column::ContestId.eq(req.contest_id))
+                .filter(user_contest::Column::UserId.eq(user_id))
+                .filter(user_contest::Column::Banned.eq(false))
+                .one(&txn)
+                .instrument(info_span!("fetch_pivot").or_current())
+                .await?
+                .ok_or(Error::NotInDB)?;
+
+            if pivot.team_id.is_some() {
+                return Err(Error::AlreadyExist("team"));
+            }
 
+            let team = team::ActiveModel {
+                contest_id: ActiveValue::Set(req.contest_id),
+                name: ActiveValue::Set(req.name),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .instrument(info_span!("insert_team").or_current())
+            .await?;
+
+            let mut pivot = pivot.into_active_model();
+            pivot.team_id = ActiveValue::Set(Some(team.id));
             pivot
-                .save(self.db.deref())
-                .instrument(info_span!("insert_pviot").or_current())
+                .update(&txn)
+                .instrument(info_span!("update_pivot").or_current())
+                .await?;
+
+            txn.commit().await.map_err(|_| Error::Retry)?;
+
+            debug!(team_id = team.id, contest_id = req.contest_id);
+            Ok(team.id.into())
+        })
+        .await
+        .with_grpc()
+        .into()
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Contest/create_invite",
+        err(level = "debug", Display)
+    )]
+    async fn create_invite(
+        &self,
+        req: Request<CreateInviteRequest>,
+    ) -> Result<Response<Invite>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        let (user_id, _) = auth.assume_login()?;
+
+        req.get_or_insert(|req| async move {
+            let contest = Entity::read_by_id(req.contest_id, &auth)?
+                .one(self.db.deref())
+                .instrument(info_span!("fetch").or_current())
+                .await?
+                .ok_or(Error::NotInDB)?;
+
+            let by_host = Entity::writable(&contest, &auth);
+            match (by_host, req.team_id) {
+                (true, Some(team_id)) => {
+                    team::Entity::find_by_id(team_id)
+                        .filter(team::Column::ContestId.eq(contest.id))
+                        .one(self.db.deref())
+                        .instrument(info_span!("fetch_team").or_current())
+                        .await?
+                        .ok_or(Error::NotInDB)?;
+                }
+                (true, None) => {}
+                (false, Some(team_id)) => {
+                    user_contest::Entity::find()
+                        .filter(user_contest::Column::ContestId.eq(contest.id))
+                        .filter(user_contest::Column::UserId.eq(user_id))
+                        .filter(user_contest::Column::TeamId.eq(team_id))
+                        .filter(user_contest::Column::Banned.eq(false))
+                        .one(self.db.deref())
+                        .instrument(info_span!("fetch_pivot").or_current())
+                        .await?
+                        .ok_or(Error::PermissionDeny("not a member of the team"))?;
+                }
+                (false, None) => {
+                    return Err(Error::PermissionDeny("only host can invite without team"))
+                }
+            }
+
+            let code = Uuid::new_v4().simple().to_string();
+            contest_invite::ActiveModel {
+                contest_id: ActiveValue::Set(contest.id),
+                team_id: ActiveValue::Set(req.team_id),
+                code: ActiveValue::Set(code.clone()),
+                by_host: ActiveValue::Set(by_host),
+                ..Default::default()
+            }
+            .save(self.db.deref())
+            .instrument(info_span!("insert_invite").or_current())
+            .await?;
+
+            Ok(Invite { code })
+        })
+        .await
+        .with_grpc()
+        .into()
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Contest/list_participant",
+        err(level = "debug", Display)
+    )]
+    async fn list_participant(
+        &self,
+        req: Request<Id>,
+    ) -> Result<Response<ListParticipantResponse>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+
+        let contest = Entity::find_by_id(req.id)
+            .with_auth(&auth)
+            .write()?
+            .one(self.db.deref())
+            .instrument(info_span!("fetch").or_current())
+            .await
+            .map_err(Into::<Error>::into)?
+            .ok_or(Error::NotInDB)?;
+
+        let teams: HashMap<i32, String> = team::Entity::find()
+            .filter(team::Column::ContestId.eq(contest.id))
+            .select_only()
+            .columns([team::Column::Id, team::Column::Name])
+            .into_tuple::<(i32, String)>()
+            .all(self.db.deref())
+            .instrument(info_span!("fetch_team").or_current())
+            .await
+            .map_err(Into::<Error>::into)?
+            .into_iter()
+            .collect();
+
+        let list: Vec<(
+            i32,
+            String,
+            Option<i32>,
+            bool,
+            u32,
+            Option<chrono::NaiveDateTime>,
+        )> = user_contest::Entity::find()
+            .filter(user_contest::Column::ContestId.eq(contest.id))
+            .inner_join(user::Entity)
+            .order_by_asc(user_contest::Column::UserId)
+            .select_only()
+            .column(user_contest::Column::UserId)
+            .column(user::Column::Username)
+            .columns([
+                user_contest::Column::TeamId,
+                user_contest::Column::Banned,
+                user_contest::Column::Score,
+                user_contest::Column::VirtualBegin,
+            ])
+            .into_tuple()
+            .all(self.db.deref())
+            .instrument(info_span!("fetch_pivot").or_current())
+            .await
+            .map_err(Into::<Error>::into)?;
+
+        Ok(Response::new(ListParticipantResponse {
+            list: list
+                .into_iter()
+                .map(
+                    |(user_id, username, team_id, banned, score, virtual_begin)| Participant {
+                        user_id,
+                        username,
+                        team_id,
+                        team_name: team_id.and_then(|x| teams.get(&x).cloned()),
+                        banned,
+                        score,
+                        virtual_begin: virtual_begin.map(into_prost),
+                    },
+                )
+                .collect(),
+        }))
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Contest/remove_participant",
+        err(level = "debug", Display)
+    )]
+    async fn remove_participant(
+        &self,
+        req: Request<RemoveParticipantRequest>,
+    ) -> Result<Response<()>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+
+        req.get_or_insert(|req| async move {
+            let contest = Entity::find_by_id(req.contest_id)
+                .with_auth(&auth)
+                .write()?
+                .one(self.db.deref())
+                .instrument(info_span!("fetch").or_current())
                 .await
-                .map_err(Into::<Error>::into)?;
+                .map_err(Into::<Error>::into)?
+                .ok_or(Error::NotInDB)?;
 
-            debug!(user_id = user_id, contest_id = req.id);
+            let pivot = user_contest::Entity::find()
+                .filter(user_contest::Column::ContestId.eq(contest.id))
+                .filter(user_contest::Column::UserId.eq(req.user_id))
+                .one(self.db.deref())
+                .instrument(info_span!("fetch_pivot").or_current())
+                .await?;
+
+            match (pivot, req.ban) {
+                (Some(pivot), true) => {
+                    let mut pivot = pivot.into_active_model();
+                    pivot.banned = ActiveValue::Set(true);
+                    pivot.team_id = ActiveValue::Set(None);
+                    pivot
+                        .update(self.db.deref())
+                        .instrument(info_span!("update_pivot").or_current())
+                        .await?;
+                }
+                (Some(pivot), false) => {
+                    pivot
+                        .delete(self.db.deref())
+                        .instrument(info_span!("remove_pivot").or_current())
+                        .await?;
+                }
+                // ban user who hasn't joined yet
+                (None, true) => {
+                    user_contest::ActiveModel {
+                        user_id: ActiveValue::Set(req.user_id),
+                        contest_id: ActiveValue::Set(contest.id),
+                        banned: ActiveValue::Set(true),
+                        ..Default::default()
+                    }
+                    .save(self.db.deref())
+                    .instrument(info_span!("insert_pivot").or_current())
+                    .await?;
+                }
+                (None, false) => return Err(Error::NotInDB),
+            }
+
+            info!(
+                user_id = req.user_id,
+                contest_id = contest.id,
+                ban = req.ban,
+                "remove_participant"
+            );
             Ok(())
         })
         .await
         .with_grpc()
         .into()
     }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Contest/scoreboard",
+        err(level = "debug", Display)
+    )]
+    async fn scoreboard(
+        &self,
+        req: Request<ScoreboardRequest>,
+    ) -> Result<Response<ScoreboardResponse>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        req.bound_check()?;
+
+        let paginator = match req.request.ok_or(Error::NotInPayload("request"))? {
+            scoreboard_request::Request::ContestId(x) => {
+                scoreboard::Paginator::new(x, req.include_virtual.unwrap_or_default())
+            }
+            scoreboard_request::Request::Paginator(x) => self.crypto.decode(x)?,
+        };
+        let mut paginator = paginator.with_auth(&auth).with_db(&self.db);
+
+        let list = paginator
+            .fetch(req.size, req.offset)
+            .in_current_span()
+            .await?;
+        let remain = paginator.remain().in_current_span().await?;
+
+        let paginator = paginator.into_inner();
+
+        Ok(Response::new(ScoreboardResponse {
+            list: list.into_iter().map(Into::into).collect(),
+            paginator: self.crypto.encode(paginator)?,
+            remain,
+        }))
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Contest/watch",
+        err(level = "debug", Display)
+    )]
+    async fn watch(&self, req: Request<Id>) -> Result<Response<Self::WatchStream>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+
+        let contest = Entity::read_by_id(req.id, &auth)?
+            .one(self.db.deref())
+            .instrument(info_span!("fetch").or_current())
+            .await
+            .map_err(Into::<Error>::into)?
+            .ok_or(Error::NotInDB)?;
+
+        // subscribe before taking snapshot, so no event is missed in between
+        let rx = self.judger.watch(contest.id);
+        let watcher = Arc::new(Watcher {
+            contest_id: contest.id,
+            auth,
+            db: self.db.clone(),
+        });
+        let snapshot = watcher.resync().in_current_span().await?;
+
+        let events = BroadcastStream::new(rx).then(move |event| {
+            let watcher = watcher.clone();
+            async move {
+                match event {
+                    Ok(event) => watcher.submission(event).await,
+                    Err(BroadcastStreamRecvError::Lagged(x)) => {
+                        debug!(lagged = x, "resync");
+                        watcher.resync().await
+                    }
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(
+            tokio_stream::once(Ok(snapshot)).chain(events),
+        )))
+    }
 }
@@ -5,9 +5,11 @@
 //! It's a decision to avoid coupling between each endpoint
 mod announcement;
 mod chat;
+mod clarification;
 mod contest;
 mod education;
 mod imgur;
+mod playground;
 mod problem;
 mod submit;
 mod testcase;
@@ -0,0 +1,68 @@
+use super::*;
+
+use std::num::NonZeroU32;
+
+use crate::controller::judger::PlaygroundPayload;
+use crate::util::rate_limit::RateLimit;
+use grpc::backend::playground_server::*;
+
+#[async_trait]
+impl Playground for ArcServer {
+    type RunStream = TonicStream<PlaygroundResult>;
+
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Playground/run",
+        err(level = "debug", Display)
+    )]
+    async fn run(
+        &self,
+        req: Request<PlaygroundRequest>,
+    ) -> Result<Response<Self::RunStream>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        let (user_id, _) = auth.assume_login()?;
+
+        req.bound_check()?;
+
+        let lang =
+            Uuid::parse_str(req.lang_uid.as_str()).map_err(|_| Error::BadArgument("lang_uid"))?;
+
+        let payload = PlaygroundPayload {
+            input: req.input,
+            code: req.code,
+            lang,
+        };
+        let result = self
+            .judger
+            .playground(user_id, payload)
+            .in_current_span()
+            .await
+            .map_err(Into::<Error>::into)?;
+
+        Ok(Response::new(result))
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Playground/send_stdin",
+        err(level = "debug", Display)
+    )]
+    async fn send_stdin(&self, req: Request<SendStdinRequest>) -> Result<Response<()>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        let (user_id, _) = auth.assume_login()?;
+
+        req.bound_check()?;
+
+        let session = Uuid::parse_str(req.session_id.as_str())
+            .map_err(|_| Error::BadArgument("session_id"))?;
+
+        self.judger
+            .send_stdin(user_id, session, req.chunk)
+            .in_current_span()
+            .await
+            .map_err(Into::<Error>::into)?;
+
+        Ok(Response::new(()))
+    }
+}
@@ -1,8 +1,45 @@
 use super::*;
 use grpc::backend::problem_server::*;
 use std::sync::Arc;
+use tokio_stream::StreamExt;
 
-use crate::entity::{contest, problem::Paginator, problem::*};
+use crate::entity::{contest, problem::Paginator, problem::*, problem_revision, tag, testcase};
+use prost::Message;
+
+/// max size of package uploaded by `Problem.UploadPackage`
+const MAX_PACKAGE_SIZE: usize = 256 * 1024 * 1024;
+/// max number of testcases in a package
+const MAX_PACKAGE_TESTCASE: usize = 1024;
+
+impl From<problem_revision::Model> for RevisionInfo {
+    fn from(value: problem_revision::Model) -> Self {
+        RevisionInfo {
+            id: value.id,
+            problem_id: value.problem_id,
+            revision: value.revision,
+            user_id: value.user_id,
+            title: value.title,
+            time: value.time,
+            memory: value.memory,
+            match_rule: value.match_rule,
+            create_at: into_prost(value.create_at),
+        }
+    }
+}
+
+impl From<problem_revision::Line<'_>> for revision_diff::Line {
+    fn from(value: problem_revision::Line<'_>) -> Self {
+        let (kind, text) = match value {
+            problem_revision::Line::Equal(x) => (revision_diff::line::Kind::Equal, x),
+            problem_revision::Line::Insert(x) => (revision_diff::line::Kind::Insert, x),
+            problem_revision::Line::Delete(x) => (revision_diff::line::Kind::Delete, x),
+        };
+        revision_diff::Line {
+            kind: kind as i32,
+            text: text.to_string(),
+        }
+    }
+}
 
 impl<'a> From<WithAuth<'a, Model>> for ProblemFullInfo {
     fn from(value: WithAuth<'a, Model>) -> Self {
@@ -27,6 +64,8 @@ impl<'a> From<WithAuth<'a, Model>> for ProblemFullInfo {
             },
             author: model.user_id,
             writable,
+            input_file: model.input_file,
+            output_file: model.output_file,
         }
     }
 }
@@ -50,6 +89,8 @@ impl From<PartialModel> for ProblemInfo {
 
 #[async_trait]
 impl Problem for ArcServer {
+    type ExportStream = TonicStream<ProblemPackage>;
+
     #[instrument(
         skip_all,
         level = "info",
@@ -137,20 +178,34 @@ impl Problem for ArcServer {
             model.user_id = ActiveValue::Set(user_id);
 
             fill_active_model!(
-                model, req.info, title, difficulty, time, memory, content, match_rule, order
+                model,
+                req.info,
+                title,
+                difficulty,
+                time,
+                memory,
+                content,
+                match_rule,
+                order,
+                input_file,
+                output_file
             );
 
             let txn = self.db.begin().await?;
 
             let model = model
-                .save(&txn)
+                .insert(&txn)
                 .instrument(info_span!("save").or_current())
                 .await
                 .map_err(Into::<Error>::into)?;
 
+            problem_revision::snapshot(&model, user_id, &txn)
+                .instrument(info_span!("snapshot").or_current())
+                .await?;
+
             let txn = Arc::new(txn);
 
-            let id = model.id.unwrap();
+            let id = model.id;
 
             insert_tag(txn.clone(), req.info.tags.into_iter(), id).await?;
 
@@ -178,26 +233,45 @@ impl Problem for ArcServer {
         let (auth, req) = self.rate_limit(req).in_current_span().await?;
 
         req.bound_check()?;
+        let (user_id, _) = auth.assume_login()?;
 
         req.get_or_insert(|req| async move {
+            let txn = self.db.begin().await?;
+
             let mut model = Entity::find_by_id(req.id)
                 .with_auth(&auth)
                 .write()?
-                .one(self.db.deref())
+                .one(&txn)
                 .await
                 .map_err(Into::<Error>::into)?
                 .ok_or(Error::NotInDB)?
                 .into_active_model();
 
             fill_exist_active_model!(
-                model, req.info, title, difficulty, time, memory, content, match_rule, order
+                model,
+                req.info,
+                title,
+                difficulty,
+                time,
+                memory,
+                content,
+                match_rule,
+                order,
+                input_file,
+                output_file
             );
             // FIXME: fill tag
 
-            model
-                .update(self.db.deref())
+            let model = model
+                .update(&txn)
                 .instrument(info_span!("update").or_current())
                 .await?;
+
+            problem_revision::snapshot(&model, user_id, &txn)
+                .instrument(info_span!("snapshot").or_current())
+                .await?;
+
+            txn.commit().await.map_err(|_| Error::Retry)?;
             Ok(())
         })
         .await
@@ -463,4 +537,391 @@ impl Problem for ArcServer {
 
         Ok(Response::new(model.with_auth(&auth).into()))
     }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Problem/list_revision",
+        err(level = "debug", Display)
+    )]
+    async fn list_revision(
+        &self,
+        req: Request<Id>,
+    ) -> Result<Response<ListRevisionResponse>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+
+        let problem = Entity::find_by_id(req.id)
+            .with_auth(&auth)
+            .write()?
+            .one(self.db.deref())
+            .instrument(info_span!("fetch").or_current())
+            .await
+            .map_err(Into::<Error>::into)?
+            .ok_or(Error::NotInDB)?;
+
+        let list = problem_revision::Entity::find()
+            .filter(problem_revision::Column::ProblemId.eq(problem.id))
+            .order_by_desc(problem_revision::Column::Revision)
+            .all(self.db.deref())
+            .instrument(info_span!("fetch_revision").or_current())
+            .await
+            .map_err(Into::<Error>::into)?;
+
+        Ok(Response::new(ListRevisionResponse {
+            list: list.into_iter().map(Into::into).collect(),
+        }))
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Problem/diff_revision",
+        err(level = "debug", Display)
+    )]
+    async fn diff_revision(
+        &self,
+        req: Request<DiffRevisionRequest>,
+    ) -> Result<Response<RevisionDiff>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+
+        let revisions = problem_revision::Entity::find()
+            .filter(problem_revision::Column::Id.is_in([req.from, req.to]))
+            .all(self.db.deref())
+            .instrument(info_span!("fetch_revision").or_current())
+            .await
+            .map_err(Into::<Error>::into)?;
+        let find = |id: i32| revisions.iter().find(|x| x.id == id).ok_or(Error::NotInDB);
+        let (old, new) = (find(req.from)?, find(req.to)?);
+
+        if old.problem_id != new.problem_id {
+            return Err(Error::BadArgument("to").into());
+        }
+        Entity::find_by_id(old.problem_id)
+            .with_auth(&auth)
+            .write()?
+            .one(self.db.deref())
+            .instrument(info_span!("fetch").or_current())
+            .await
+            .map_err(Into::<Error>::into)?
+            .ok_or(Error::NotInDB)?;
+
+        Ok(Response::new(RevisionDiff {
+            changes: problem_revision::diff_fields(old, new)
+                .into_iter()
+                .map(|x| revision_diff::Change {
+                    field: x.field.to_string(),
+                    old: x.old,
+                    new: x.new,
+                })
+                .collect(),
+            content: problem_revision::diff_lines(&old.content, &new.content)
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }))
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Problem/restore_revision",
+        err(level = "debug", Display)
+    )]
+    async fn restore_revision(
+        &self,
+        req: Request<RestoreRevisionRequest>,
+    ) -> Result<Response<()>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        let (user_id, _) = auth.assume_login()?;
+
+        req.get_or_insert(|req| async move {
+            let txn = self.db.begin().await?;
+
+            let model = Entity::find_by_id(req.problem_id)
+                .with_auth(&auth)
+                .write()?
+                .one(&txn)
+                .instrument(info_span!("fetch").or_current())
+                .await
+                .map_err(Into::<Error>::into)?
+                .ok_or(Error::NotInDB)?;
+
+            let revision = problem_revision::Entity::find_by_id(req.revision_id)
+                .filter(problem_revision::Column::ProblemId.eq(model.id))
+                .one(&txn)
+                .instrument(info_span!("fetch_revision").or_current())
+                .await
+                .map_err(Into::<Error>::into)?
+                .ok_or(Error::NotInDB)?;
+
+            let mut model = model.into_active_model();
+            revision.restore(&mut model);
+            let model = model
+                .update(&txn)
+                .instrument(info_span!("update").or_current())
+                .await?;
+
+            problem_revision::snapshot(&model, user_id, &txn)
+                .instrument(info_span!("snapshot").or_current())
+                .await?;
+
+            txn.commit().await.map_err(|_| Error::Retry)?;
+
+            info!(
+                problem_id = model.id,
+                revision = revision.revision,
+                "revision_restored"
+            );
+            Ok(())
+        })
+        .await
+        .with_grpc()
+        .into()
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Problem/export",
+        err(level = "debug", Display)
+    )]
+    async fn export(&self, req: Request<Id>) -> Result<Response<Self::ExportStream>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+
+        let model = Entity::find_by_id(req.id)
+            .with_auth(&auth)
+            .write()?
+            .one(self.db.deref())
+            .instrument(info_span!("fetch").or_current())
+            .await
+            .map_err(Into::<Error>::into)?
+            .ok_or(Error::NotInDB)?;
+
+        let tags = model
+            .find_related(tag::Entity)
+            .all(self.db.deref())
+            .instrument(info_span!("fetch_tag").or_current())
+            .await
+            .map_err(Into::<Error>::into)?;
+
+        // testcases are fetched one by one to avoid holding all of them in memory
+        let testcase_ids: Vec<i32> = testcase::Entity::find()
+            .filter(testcase::Column::ProblemId.eq(model.id))
+            .order_by_asc(testcase::Column::Order)
+            .select_only()
+            .column(testcase::Column::Id)
+            .into_tuple()
+            .all(self.db.deref())
+            .instrument(info_span!("fetch_testcase").or_current())
+            .await
+            .map_err(Into::<Error>::into)?;
+
+        let checker = match (model.checker_lang, model.checker) {
+            (Some(lang_uid), Some(code)) => Some(problem_package::Checker { lang_uid, code }),
+            _ => None,
+        };
+        let meta = problem_package::Meta {
+            title: model.title,
+            difficulty: model.difficulty,
+            time: model.time as u64,
+            memory: model.memory as u64,
+            content: model.content,
+            match_rule: model.match_rule,
+            order: model.order,
+            tags: tags.into_iter().map(|x| x.name).collect(),
+            input_file: model.input_file,
+            output_file: model.output_file,
+            checker,
+        };
+
+        let db = self.db.clone();
+        let testcases = tokio_stream::iter(testcase_ids).then(move |id| {
+            let db = db.clone();
+            async move {
+                let model = testcase::Entity::find_by_id(id)
+                    .one(db.deref())
+                    .await
+                    .map_err(Into::<Error>::into)?
+                    .ok_or(Error::NotInDB)?;
+                Ok::<_, Status>(ProblemPackage {
+                    part: Some(problem_package::Part::Testcase(problem_package::Testcase {
+                        input: model.input,
+                        output: model.output,
+                        score: model.score,
+                    })),
+                })
+            }
+        });
+
+        Ok(Response::new(Box::pin(
+            tokio_stream::once(Ok(ProblemPackage {
+                part: Some(problem_package::Part::Meta(meta)),
+            }))
+            .chain(testcases),
+        )))
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Problem/upload_package",
+        err(level = "debug", Display)
+    )]
+    async fn upload_package(
+        &self,
+        req: Request<UploadChunkRequest>,
+    ) -> Result<Response<UploadChunkResponse>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        let (user_id, perm) = auth.assume_login()?;
+        perm.super_user()?;
+
+        req.bound_check()?;
+
+        let upload_id = req
+            .upload_id
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| Error::BadArgument("upload_id"))?;
+        let (upload_id, received) = self
+            .upload
+            .append(user_id, upload_id, req.chunk, MAX_PACKAGE_SIZE)
+            .map_err(Into::<Error>::into)?;
+
+        Ok(Response::new(UploadChunkResponse {
+            upload_id: upload_id.to_string(),
+            received,
+        }))
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Problem/import",
+        err(level = "debug", Display)
+    )]
+    async fn import(&self, req: Request<ImportProblemRequest>) -> Result<Response<Id>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        let (user_id, perm) = auth.assume_login()?;
+        perm.super_user()?;
+
+        req.get_or_insert(|req| async move {
+            let upload_id =
+                Uuid::parse_str(&req.upload_id).map_err(|_| Error::BadArgument("upload_id"))?;
+            let buf = self.upload.take(user_id, upload_id)?;
+
+            // validate the whole package before touching database,
+            // rejected package is kept so client can retry
+            let (buf, result) = tokio::task::spawn_blocking(move || {
+                let result = decode_package(&buf).and_then(|(meta, cases)| {
+                    if meta.check() || cases.iter().any(BoundCheck::check) {
+                        return Err(Error::NumberTooLarge);
+                    }
+                    if let Some(checker) = &meta.checker {
+                        Uuid::parse_str(&checker.lang_uid)
+                            .map_err(|_| Error::BadArgument("lang_uid"))?;
+                    }
+                    Ok((meta, cases))
+                });
+                (buf, result)
+            })
+            .await
+            .map_err(|_| Error::Unreachable("package decoder panicked"))?;
+            let (meta, cases) = match result {
+                Ok(x) => x,
+                Err(err) => {
+                    self.upload.restore(user_id, upload_id, buf);
+                    return Err(err);
+                }
+            };
+            drop(buf);
+
+            let mut model: ActiveModel = Default::default();
+            model.user_id = ActiveValue::Set(user_id);
+
+            if let Some(checker) = meta.checker {
+                model.checker_lang = ActiveValue::Set(Some(checker.lang_uid));
+                model.checker = ActiveValue::Set(Some(checker.code));
+            }
+
+            fill_active_model!(
+                model,
+                meta,
+                title,
+                difficulty,
+                time,
+                memory,
+                content,
+                match_rule,
+                order,
+                input_file,
+                output_file
+            );
+
+            let txn = self.db.begin().await?;
+
+            let model = model
+                .insert(&txn)
+                .instrument(info_span!("save").or_current())
+                .await?;
+
+            problem_revision::snapshot(&model, user_id, &txn)
+                .instrument(info_span!("snapshot").or_current())
+                .await?;
+
+            let count = cases.len();
+            for (i, case) in cases.into_iter().enumerate() {
+                testcase::ActiveModel {
+                    user_id: ActiveValue::Set(user_id),
+                    problem_id: ActiveValue::Set(Some(model.id)),
+                    input: ActiveValue::Set(case.input),
+                    output: ActiveValue::Set(case.output),
+                    score: ActiveValue::Set(case.score),
+                    order: ActiveValue::Set((i + 1) as f32),
+                    ..Default::default()
+                }
+                .insert(&txn)
+                .instrument(info_span!("save_testcase").or_current())
+                .await?;
+            }
+
+            let txn = Arc::new(txn);
+
+            insert_tag(txn.clone(), meta.tags.into_iter(), model.id).await?;
+
+            Arc::try_unwrap(txn)
+                .unwrap()
+                .commit()
+                .await
+                .map_err(|_| Error::Retry)?;
+
+            info!(count.problem.count = 1, id = model.id, testcase = count);
+
+            Ok(model.id.into())
+        })
+        .await
+        .with_grpc()
+        .into()
+    }
+}
+
+/// decode length-delimited [`ProblemPackage`], `meta` come first
+fn decode_package(
+    mut buf: &[u8],
+) -> Result<(problem_package::Meta, Vec<problem_package::Testcase>), Error> {
+    let mut parts = Vec::new();
+    while !buf.is_empty() {
+        if parts.len() > MAX_PACKAGE_TESTCASE {
+            return Err(Error::BadArgument("testcase"));
+        }
+        let part = ProblemPackage::decode_length_delimited(&mut buf)
+            .map_err(|_| Error::BadArgument("package"))?;
+        parts.push(part.part.ok_or(Error::NotInPayload("part"))?);
+    }
+
+    let mut parts = parts.into_iter();
+    let Some(problem_package::Part::Meta(meta)) = parts.next() else {
+        return Err(Error::NotInPayload("meta"));
+    };
+    let cases = parts
+        .map(|x| match x {
+            problem_package::Part::Testcase(x) => Ok(x),
+            _ => Err(Error::BadArgument("part")),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((meta, cases))
 }
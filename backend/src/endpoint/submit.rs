@@ -1,15 +1,69 @@
 use super::*;
 
-use crate::controller::judger::SubmitBuilder;
-use crate::util::code::Code;
+use crate::controller::judger::{Judger, SubmitBuilder};
+use crate::util::{auth::Auth, code::Code};
 use grpc::backend::{submit_server::*, StateCode as BackendCode};
 
 use crate::entity::{
     contest, problem, submit,
     submit::{Paginator, *},
-    user,
+    user_contest,
 };
-use tokio_stream::wrappers::ReceiverStream;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, StreamExt};
+
+/// persisted state of a submit, `None` if the submit doesn't exist or can't be followed
+async fn persisted_state(
+    id: i32,
+    auth: &Auth,
+    db: &DatabaseConnection,
+) -> Result<Option<SubmitStatus>, Error> {
+    let state: Option<(bool, Option<u32>, i32)> = follow_filter(Entity::find_by_id(id), auth)?
+        .select_only()
+        .columns([Column::Committed, Column::Status, Column::PassCase])
+        .into_tuple()
+        .one(db)
+        .await?;
+    Ok(state.map(|(committed, status, pass_case)| match committed {
+        true => status
+            .and_then(|x| Code::try_from(x).ok())
+            .unwrap_or(Code::Unknown)
+            .into(),
+        false => SubmitStatus {
+            task: Some(submit_status::Task::Case(pass_case)),
+        },
+    }))
+}
+
+fn is_result(state: &SubmitStatus) -> bool {
+    matches!(state.task, Some(submit_status::Task::Result(_)))
+}
+
+/// forward live events of a submit until its result
+///
+/// the result might be committed before subscribing, or in between,
+/// so persisted state is checked again once live events end
+async fn follow_live(
+    id: i32,
+    auth: Auth,
+    judger: Arc<Judger>,
+    db: Arc<DatabaseConnection>,
+    tx: mpsc::Sender<Result<SubmitStatus, Status>>,
+) -> Result<(), Error> {
+    if let Some(mut live) = judger.follow(id) {
+        while let Some(state) = live.next().await {
+            let done = matches!(&state, Ok(x) if is_result(x));
+            if tx.send(state).await.is_err() || done {
+                return Ok(());
+            }
+        }
+    }
+    if let Some(state) = persisted_state(id, &auth, &db).await?.filter(is_result) {
+        tx.send(Ok(state)).await.ok();
+    }
+    Ok(())
+}
 
 impl From<Model> for SubmitInfo {
     fn from(value: Model) -> Self {
@@ -27,7 +81,11 @@ impl From<Model> for SubmitInfo {
                 accuracy: value.accuracy.map(|x| x as u64),
                 time: value.time.map(|x| x as u64),
                 memory: value.memory.map(|x| x as u64),
+                exit_code: value.exit_code,
+                signal: value.signal,
             },
+            stderr: None,
+            revision_id: value.revision_id,
         }
     }
 }
@@ -45,7 +103,11 @@ impl From<PartialModel> for SubmitInfo {
                 accuracy: value.accuracy.map(|x| x as u64),
                 time: value.time.map(|x| x as u64),
                 memory: value.memory.map(|x| x as u64),
+                exit_code: value.exit_code,
+                signal: value.signal,
             },
+            stderr: None,
+            revision_id: value.revision_id,
         }
     }
 }
@@ -96,14 +158,28 @@ impl Submit for ArcServer {
 
         debug!(id = req.id);
 
-        let model = Entity::read_filter(Entity::find_by_id(req.id), &auth)?
+        let mut model = Entity::read_filter(Entity::find_by_id(req.id), &auth)?
             .one(self.db.deref())
             .instrument(debug_span!("fetch").or_current())
             .await
             .map_err(Into::<Error>::into)?
             .ok_or(Error::NotInDB)?;
 
-        Ok(Response::new(model.into()))
+        // stderr is only visible to problem owner, for debugging testcases
+        let stderr = match model.stderr.take() {
+            Some(stderr) => problem::Entity::find_by_id(model.problem_id)
+                .one(self.db.deref())
+                .instrument(debug_span!("fetch_problem").or_current())
+                .await
+                .map_err(Into::<Error>::into)?
+                .filter(|problem| problem::Entity::writable(problem, &auth))
+                .map(|_| stderr),
+            None => None,
+        };
+
+        let mut info: SubmitInfo = model.into();
+        info.stderr = stderr;
+        Ok(Response::new(info))
     }
     #[instrument(
         skip_all,
@@ -129,19 +205,24 @@ impl Submit for ArcServer {
                 .ok_or(Error::NotInDB)?;
 
             if (problem.user_id != user_id) && (!problem.public) {
-                problem
+                let contest = problem
                     .find_related(contest::Entity)
                     .one(self.db.deref())
                     .instrument(info_span!("fetch_contest").or_current())
                     .await
                     .map_err(Into::<Error>::into)?
-                    .ok_or(Error::NotInDB)?
-                    .find_related(user::Entity)
-                    .one(self.db.deref())
-                    .instrument(info_span!("fetch_user").or_current())
-                    .await
-                    .map_err(Into::<Error>::into)?
                     .ok_or(Error::NotInDB)?;
+                if contest.host != user_id {
+                    user_contest::Entity::find()
+                        .filter(user_contest::Column::ContestId.eq(contest.id))
+                        .filter(user_contest::Column::UserId.eq(user_id))
+                        .filter(user_contest::Column::Banned.eq(false))
+                        .one(self.db.deref())
+                        .instrument(info_span!("fetch_pivot").or_current())
+                        .await
+                        .map_err(Into::<Error>::into)?
+                        .ok_or(Error::NotInDB)?;
+                }
             }
 
             let submit = SubmitBuilder::default()
@@ -211,14 +292,29 @@ impl Submit for ArcServer {
         err(level = "debug", Display)
     )]
     async fn follow(&self, req: Request<Id>) -> Result<Response<Self::FollowStream>, Status> {
-        let (_, req) = self.rate_limit(req).in_current_span().await?;
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
 
-        Ok(Response::new(self.judger.follow(req.id).unwrap_or_else(
-            || {
-                Box::pin(ReceiverStream::new(tokio::sync::mpsc::channel(16).1))
-                    as Self::FollowStream
-            },
-        )))
+        let state = persisted_state(req.id, &auth, &self.db)
+            .in_current_span()
+            .await?
+            .ok_or(Error::NotInDB)?;
+
+        let (tx, rx) = mpsc::channel(16);
+        let done = is_result(&state);
+        tx.send(Ok(state)).await.ok();
+        if !done {
+            let (id, judger, db) = (req.id, self.judger.clone(), self.db.clone());
+            tokio::spawn(
+                async move {
+                    if let Err(err) = follow_live(id, auth, judger, db, tx).await {
+                        tracing::debug!(err = err.to_string(), "follow_fail");
+                    }
+                }
+                .in_current_span(),
+            );
+        }
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
 
     #[instrument(
@@ -1,11 +1,20 @@
 use super::*;
 
 use grpc::backend::testcase_server::*;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Semaphore};
+use tokio_stream::wrappers::ReceiverStream;
 
 use crate::entity::{
     problem,
     testcase::{Paginator, *},
 };
+use crate::util::archive;
+
+/// max size of archive uploaded by `Testcase.UploadArchive`
+const MAX_ARCHIVE_SIZE: usize = 256 * 1024 * 1024;
+/// max number of archives parsed at the same time
+static PARSE_PERMIT: Semaphore = Semaphore::const_new(2);
 
 impl<'a> From<WithAuth<'a, Model>> for TestcaseFullInfo {
     fn from(value: WithAuth<'a, Model>) -> Self {
@@ -32,8 +41,122 @@ impl From<PartialModel> for TestcaseInfo {
     }
 }
 
+fn progress(x: upload_archive_progress::Progress) -> Result<UploadArchiveProgress, Status> {
+    Ok(UploadArchiveProgress { progress: Some(x) })
+}
+
+fn file_progress(
+    name: String,
+    result: upload_archive_progress::file::Result,
+) -> Result<UploadArchiveProgress, Status> {
+    progress(upload_archive_progress::Progress::File(
+        upload_archive_progress::File {
+            name,
+            result: Some(result),
+        },
+    ))
+}
+
+/// parse the uploaded archive, the content is returned along with the plan
+async fn plan_archive(
+    req: &CommitArchiveRequest,
+    buf: Vec<u8>,
+) -> Result<(Vec<u8>, Result<archive::Plan, Error>), Error> {
+    let format = match req.format() {
+        commit_archive_request::Format::Zip => archive::Format::Zip,
+        commit_archive_request::Format::Tar => archive::Format::Tar,
+    };
+    let total_score = req.total_score.unwrap_or(100);
+    let _permit = PARSE_PERMIT
+        .acquire()
+        .await
+        .map_err(|_| Error::Unreachable("parse semaphore closed"))?;
+    let (buf, plan) = tokio::task::spawn_blocking(move || {
+        let plan = archive::plan(format, &buf, total_score);
+        (buf, plan)
+    })
+    .await
+    .map_err(|_| Error::Unreachable("archive parser panicked"))?;
+    let plan = plan.map_err(|err| {
+        tracing::debug!(err = err.to_string(), "archive_invalid");
+        Error::BadArgument("archive")
+    });
+    Ok((buf, plan))
+}
+
+/// create all testcases of the plan in one transaction
+async fn commit_archive(
+    user_id: i32,
+    problem_id: i32,
+    plan: archive::Plan,
+    db: Arc<DatabaseConnection>,
+    tx: mpsc::Sender<Result<UploadArchiveProgress, Status>>,
+) -> Result<(), Error> {
+    if !plan.rejected.is_empty() || plan.cases.is_empty() {
+        for (name, err) in plan.rejected {
+            tx.send(file_progress(
+                name,
+                upload_archive_progress::file::Result::Error(err.to_string()),
+            ))
+            .await
+            .ok();
+        }
+        return Err(Error::BadArgument("archive"));
+    }
+
+    let txn = db.begin().await?;
+
+    let last: Option<Option<f32>> = Entity::find()
+        .filter(Column::ProblemId.eq(problem_id))
+        .select_only()
+        .column_as(Column::Order.max(), "max_order")
+        .into_tuple()
+        .one(&txn)
+        .await?;
+    let mut order = last.flatten().unwrap_or_default();
+
+    let mut created = Vec::with_capacity(plan.cases.len());
+    for case in plan.cases {
+        order += 1.0;
+        let id = Entity::insert(ActiveModel {
+            user_id: ActiveValue::Set(user_id),
+            problem_id: ActiveValue::Set(Some(problem_id)),
+            input: ActiveValue::Set(case.input),
+            output: ActiveValue::Set(case.output),
+            score: ActiveValue::Set(case.score),
+            order: ActiveValue::Set(order),
+            ..Default::default()
+        })
+        .exec(&txn)
+        .instrument(info_span!("save").or_current())
+        .await?
+        .last_insert_id;
+        created.push((case.name, id));
+    }
+
+    txn.commit().await.map_err(|_| Error::Retry)?;
+
+    let count = created.len() as u32;
+    info!(count.testcase.count = count, problem_id = problem_id);
+
+    for (name, id) in created {
+        tx.send(file_progress(
+            name,
+            upload_archive_progress::file::Result::TestcaseId(id),
+        ))
+        .await
+        .ok();
+    }
+    tx.send(progress(upload_archive_progress::Progress::Done(count)))
+        .await
+        .ok();
+    Ok(())
+}
+
 #[async_trait]
 impl Testcase for ArcServer {
+    type CommitArchiveStream = TonicStream<UploadArchiveProgress>;
+
     #[instrument(
         skip_all,
         level = "info",
@@ -332,4 +455,92 @@ impl Testcase for ArcServer {
 
         Ok(Response::new(model.with_auth(&auth).into()))
     }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Testcase/upload_archive",
+        err(level = "debug", Display)
+    )]
+    async fn upload_archive(
+        &self,
+        req: Request<UploadChunkRequest>,
+    ) -> Result<Response<UploadChunkResponse>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        let (user_id, perm) = auth.assume_login()?;
+        perm.super_user()?;
+
+        req.bound_check()?;
+
+        let upload_id = req
+            .upload_id
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .map_err(|_| Error::BadArgument("upload_id"))?;
+        let (upload_id, received) = self
+            .upload
+            .append(user_id, upload_id, req.chunk, MAX_ARCHIVE_SIZE)
+            .map_err(Into::<Error>::into)?;
+
+        Ok(Response::new(UploadChunkResponse {
+            upload_id: upload_id.to_string(),
+            received,
+        }))
+    }
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.backend.Testcase/commit_archive",
+        err(level = "debug", Display)
+    )]
+    async fn commit_archive(
+        &self,
+        req: Request<CommitArchiveRequest>,
+    ) -> Result<Response<Self::CommitArchiveStream>, Status> {
+        let (auth, req) = self.rate_limit(req).in_current_span().await?;
+        let (user_id, perm) = auth.assume_login()?;
+        perm.super_user()?;
+
+        let upload_id =
+            Uuid::parse_str(&req.upload_id).map_err(|_| Error::BadArgument("upload_id"))?;
+
+        let problem: problem::IdModel = problem::Entity::write_by_id(req.problem_id, &auth)?
+            .into_partial_model()
+            .one(self.db.deref())
+            .instrument(debug_span!("find_parent").or_current())
+            .await
+            .map_err(Into::<Error>::into)?
+            .ok_or(Error::NotInDB)?;
+
+        let buf = self
+            .upload
+            .take(user_id, upload_id)
+            .map_err(Into::<Error>::into)?;
+
+        // rejected archive is kept so client can retry
+        let (buf, plan) = plan_archive(&req, buf).await?;
+        let plan = match plan {
+            Ok(plan) if plan.rejected.is_empty() && !plan.cases.is_empty() => {
+                drop(buf);
+                plan
+            }
+            plan => {
+                self.upload.restore(user_id, upload_id, buf);
+                plan?
+            }
+        };
+
+        let (tx, rx) = mpsc::channel(16);
+        let db = self.db.clone();
+        tokio::spawn(
+            async move {
+                if let Err(err) = commit_archive(user_id, problem.id, plan, db, tx.clone()).await {
+                    tx.send(Err(err.into())).await.ok();
+                }
+            }
+            .in_current_span(),
+        );
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
 }
@@ -0,0 +1,192 @@
+//! question from participant to host of contest
+//!
+//! Answer is visible to the asker only, unless host make it public
+//! to every participant.
+use sea_orm::sea_query::SimpleExpr;
+use tracing::instrument;
+
+use super::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "clarification")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub contest_id: i32,
+    pub problem_id: Option<i32>,
+    /// asker
+    pub user_id: i32,
+    pub question: String,
+    pub answer: Option<String>,
+    /// broadcast to every participant
+    pub public: bool,
+    #[sea_orm(column_type = "Time")]
+    pub create_at: chrono::NaiveDateTime,
+    #[sea_orm(column_type = "Time", nullable)]
+    pub answer_at: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::contest::Entity",
+        from = "Column::ContestId",
+        to = "super::contest::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Contest,
+    #[sea_orm(
+        belongs_to = "super::problem::Entity",
+        from = "Column::ProblemId",
+        to = "super::problem::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Problem,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<contest::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Contest.def()
+    }
+}
+
+impl Related<problem::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Problem.def()
+    }
+}
+
+impl Related<user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// whether a participant(not host) can see it
+    pub fn visible_to(&self, user_id: Option<i32>) -> bool {
+        Some(self.user_id) == user_id || (self.public && self.answer.is_some())
+    }
+}
+
+/// clarifications of contests hosted by `user_id`
+fn hosted(user_id: i32) -> SimpleExpr {
+    Column::ContestId.in_subquery(contest::hosted_contest(user_id))
+}
+
+impl Filter for Entity {
+    #[instrument(skip_all, level = "debug")]
+    fn read_filter<S: QueryFilter + Send>(query: S, auth: &Auth) -> Result<S, Error> {
+        let (user_id, perm) = auth.assume_login()?;
+        Ok(match perm {
+            RoleLv::Admin | RoleLv::Root => query,
+            _ => query.filter(
+                Column::UserId
+                    .eq(user_id)
+                    .or(Column::Public
+                        .eq(true)
+                        .and(Column::Answer.is_not_null())
+                        .and(
+                            Column::ContestId.in_subquery(contest::joined_contest(user_id, false)),
+                        ))
+                    .or(hosted(user_id)),
+            ),
+        })
+    }
+    #[instrument(skip_all, level = "debug")]
+    fn write_filter<S: QueryFilter + Send>(query: S, auth: &Auth) -> Result<S, Error> {
+        let (user_id, perm) = auth.assume_login()?;
+        Ok(match perm {
+            RoleLv::Admin | RoleLv::Root => query,
+            _ => query.filter(hosted(user_id)),
+        })
+    }
+    /// host of contest is only checked in [`Filter::write_filter`]
+    fn writable(_: &Self::Model, auth: &Auth) -> bool {
+        auth.perm() >= RoleLv::Admin
+    }
+}
+
+#[async_trait]
+impl Reflect<Entity> for Model {
+    fn get_id(&self) -> i32 {
+        self.id
+    }
+
+    async fn all(query: Select<Entity>, db: &DatabaseConnection) -> Result<Vec<Self>, Error> {
+        query.all(db).await.map_err(Into::<Error>::into)
+    }
+}
+
+pub struct ParentPagerTrait;
+
+impl PagerData for ParentPagerTrait {
+    type Data = (i32, chrono::NaiveDateTime);
+}
+
+#[async_trait]
+impl Source for ParentPagerTrait {
+    const ID: <Self::Entity as EntityTrait>::Column = Column::Id;
+    type Entity = Entity;
+    async fn filter(
+        auth: &Auth,
+        data: &Self::Data,
+        _db: &DatabaseConnection,
+    ) -> Result<Select<Self::Entity>, Error> {
+        Entity::read_filter(Entity::find(), auth).map(|x| x.filter(Column::ContestId.eq(data.0)))
+    }
+}
+
+#[async_trait]
+impl SortSource<Model> for ParentPagerTrait {
+    fn sort_col(_data: &Self::Data) -> impl ColumnTrait {
+        Column::CreateAt
+    }
+    fn get_val(data: &Self::Data) -> impl Into<Value> + Clone + Send {
+        data.1
+    }
+    fn save_val(data: &mut Self::Data, model: &Model) {
+        data.1 = model.create_at
+    }
+}
+
+type ParentPaginator = UninitPaginator<ColumnPaginator<ParentPagerTrait, Model>>;
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Paginator(ParentPaginator);
+
+impl WithAuthTrait for Paginator {}
+
+impl Paginator {
+    pub fn new(contest_id: i32, start_from_end: bool) -> Self {
+        Self(ParentPaginator::new(
+            (contest_id, Default::default()),
+            start_from_end,
+        ))
+    }
+}
+
+impl<'a, 'b> WithDB<'a, WithAuth<'b, Paginator>> {
+    pub async fn fetch(&mut self, size: u64, offset: i64) -> Result<Vec<Model>, Error> {
+        let db = self.0;
+        let auth = self.1 .0;
+        self.1 .1 .0.fetch(size, offset, auth, db).await
+    }
+    pub async fn remain(&self) -> Result<u64, Error> {
+        let db = self.0;
+        let auth = self.1 .0;
+        self.1 .1 .0.remain(auth, db).await
+    }
+}
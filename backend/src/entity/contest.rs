@@ -3,7 +3,10 @@ use std::ops::Deref;
 use crate::union;
 use chrono::Local;
 use grpc::backend::list_contest_request::Sort;
-use sea_orm::Statement;
+use sea_orm::{
+    sea_query::{Expr, Query, SelectStatement},
+    Statement,
+};
 use tracing::{instrument, Instrument};
 
 use super::*;
@@ -28,6 +31,17 @@ pub struct Model {
     #[sea_orm(column_type = "Time", on_update = "current_timestamp")]
     pub update_at: chrono::NaiveDateTime,
     pub public: bool,
+    /// [`grpc::backend::ContestRule`] of scoreboard
+    pub rule: i32,
+    /// scoreboard is frozen since then
+    #[sea_orm(column_type = "Time", nullable)]
+    pub freeze_at: Option<chrono::NaiveDateTime>,
+    /// user can join since then
+    #[sea_orm(column_type = "Time", nullable)]
+    pub register_begin: Option<chrono::NaiveDateTime>,
+    /// user can join until then
+    #[sea_orm(column_type = "Time", nullable)]
+    pub register_end: Option<chrono::NaiveDateTime>,
 }
 
 #[derive(DerivePartialModel, FromQueryResult)]
@@ -37,6 +51,9 @@ pub struct PartialModel {
     pub host: i32,
     pub begin: Option<chrono::NaiveDateTime>,
     pub end: Option<chrono::NaiveDateTime>,
+    pub freeze_at: Option<chrono::NaiveDateTime>,
+    pub register_begin: Option<chrono::NaiveDateTime>,
+    pub register_end: Option<chrono::NaiveDateTime>,
     pub title: String,
     pub password: Option<Vec<u8>>,
     pub public: bool,
@@ -72,6 +89,10 @@ impl IdModel {
             create_at: Default::default(),
             update_at: Default::default(),
             public: self.public,
+            rule: Default::default(),
+            freeze_at: Default::default(),
+            register_begin: Default::default(),
+            register_end: Default::default(),
         }
     }
 }
@@ -92,6 +113,10 @@ pub enum Relation {
     User,
     #[sea_orm(has_many = "super::user_contest::Entity")]
     UserContest,
+    #[sea_orm(has_many = "super::team::Entity")]
+    Team,
+    #[sea_orm(has_many = "super::contest_invite::Entity")]
+    ContestInvite,
 }
 
 impl Related<announcement::Entity> for Entity {
@@ -106,6 +131,18 @@ impl Related<user_contest::Entity> for Entity {
     }
 }
 
+impl Related<team::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Team.def()
+    }
+}
+
+impl Related<contest_invite::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ContestInvite.def()
+    }
+}
+
 impl Related<problem::Entity> for Entity {
     fn to() -> RelationDef {
         Relation::Problem.def()
@@ -123,6 +160,74 @@ impl Related<user::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
+impl Model {
+    /// time since which results are hidden from `auth`
+    ///
+    /// host and admin can always see the unfrozen scoreboard
+    pub fn freeze_for(&self, auth: &Auth) -> Option<chrono::NaiveDateTime> {
+        self.freeze_at.filter(|_| !Entity::writable(self, auth))
+    }
+    /// whether user can join at `now`
+    ///
+    /// Without registration window, user can only join after contest begin.
+    pub fn register_open(&self, now: chrono::NaiveDateTime) -> bool {
+        if self.register_begin.is_none() && self.register_end.is_none() {
+            return self.begin.map_or(true, |x| x <= now);
+        }
+        self.register_begin.map_or(true, |x| x <= now)
+            && self.register_end.map_or(true, |x| now < x)
+    }
+}
+
+/// contests `user_id` participates in, or has been banned from if `banned`
+pub fn joined_contest(user_id: i32, banned: bool) -> SelectStatement {
+    Query::select()
+        .column(user_contest::Column::ContestId)
+        .from(user_contest::Entity)
+        .and_where(user_contest::Column::UserId.eq(user_id))
+        .and_where(user_contest::Column::Banned.eq(banned))
+        .to_owned()
+}
+
+/// contests hosted by `user_id`
+pub fn hosted_contest(user_id: i32) -> SelectStatement {
+    Query::select()
+        .column(Column::Id)
+        .from(Entity)
+        .and_where(Column::Host.eq(user_id))
+        .to_owned()
+}
+
+/// submissions hidden from `auth` because scoreboard is frozen
+///
+/// It's submissions made after `freeze_at`, except the user's own,
+/// and host can see all submissions of the contest.
+pub fn frozen_submit(auth: &Auth) -> SelectStatement {
+    let mut query = Query::select();
+    query
+        .column((submit::Entity, submit::Column::Id))
+        .from(submit::Entity)
+        .inner_join(
+            problem::Entity,
+            Expr::col((problem::Entity, problem::Column::Id))
+                .equals((submit::Entity, submit::Column::ProblemId)),
+        )
+        .inner_join(
+            Entity,
+            Expr::col((Entity, Column::Id)).equals((problem::Entity, problem::Column::ContestId)),
+        )
+        .and_where(
+            Expr::col((submit::Entity, submit::Column::UploadAt))
+                .gte(Expr::col((Entity, Column::FreezeAt))),
+        );
+    if let Some(user_id) = auth.user_id() {
+        query
+            .and_where(Expr::col((Entity, Column::Host)).ne(user_id))
+            .and_where(Expr::col((submit::Entity, submit::Column::UserId)).ne(user_id));
+    }
+    query
+}
+
 #[tonic::async_trait]
 impl ParentalTrait<IdModel> for Entity {
     #[instrument(skip_all, level = "info")]
@@ -139,8 +244,12 @@ impl ParentalTrait<IdModel> for Entity {
 
                     union!(
                         [Column::Id, Column::Host, Column::Public, Column::Begin],
-                        user.find_related(Entity),
-                        Entity::find().filter(Column::Public.eq(true).and(Column::Begin.lte(now))),
+                        Entity::find()
+                            .filter(Column::Id.in_subquery(joined_contest(user.id, false)))
+                            .filter(Column::Begin.is_null().or(Column::Begin.lte(now))),
+                        Entity::find()
+                            .filter(Column::Public.eq(true).and(Column::Begin.lte(now)))
+                            .filter(Column::Id.not_in_subquery(joined_contest(user.id, true))),
                         Entity::find().filter(Column::Host.eq(user.id))
                     )
                     .and_where(Column::Id.eq(id))
@@ -172,11 +281,15 @@ impl Filter for Entity {
     fn read_filter<S: QueryFilter + Send>(query: S, auth: &Auth) -> Result<S, Error> {
         Ok(match auth.perm() {
             RoleLv::Guest => query.filter(Column::Public.eq(true)),
-            RoleLv::User | RoleLv::Super => query.filter(
-                Column::Public
-                    .eq(true)
-                    .or(Column::Host.eq(auth.user_id().unwrap())),
-            ),
+            RoleLv::User | RoleLv::Super => {
+                let user_id = auth.user_id().unwrap();
+                query.filter(
+                    Column::Public
+                        .eq(true)
+                        .and(Column::Id.not_in_subquery(joined_contest(user_id, true)))
+                        .or(Column::Host.eq(user_id)),
+                )
+            }
             RoleLv::Admin | RoleLv::Root => query,
         })
     }
@@ -0,0 +1,54 @@
+//! one-time invitation code of a contest
+
+use super::*;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "contest_invite")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub contest_id: i32,
+    /// join the team on use
+    pub team_id: Option<i32>,
+    #[sea_orm(unique)]
+    pub code: String,
+    /// created by host, which bypass password and registration window
+    pub by_host: bool,
+    #[sea_orm(column_type = "Time")]
+    pub create_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::contest::Entity",
+        from = "Column::ContestId",
+        to = "super::contest::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Contest,
+    #[sea_orm(
+        belongs_to = "super::team::Entity",
+        from = "Column::TeamId",
+        to = "super::team::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Team,
+}
+
+impl Related<contest::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Contest.def()
+    }
+}
+
+impl Related<team::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Team.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
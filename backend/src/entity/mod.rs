@@ -4,12 +4,17 @@
 
 pub mod announcement;
 pub mod chat;
+pub mod clarification;
 pub mod contest;
+pub mod contest_invite;
 pub mod education;
 pub mod problem;
+pub mod problem_revision;
+pub mod scoreboard;
 pub mod submit;
 pub mod tag;
 pub mod tag_problem;
+pub mod team;
 pub mod testcase;
 pub mod token;
 pub mod user;
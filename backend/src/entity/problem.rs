@@ -32,6 +32,17 @@ pub struct Model {
     pub update_at: chrono::NaiveDateTime,
     pub match_rule: i32,
     pub order: f32,
+    /// read input from the file instead of stdin
+    #[sea_orm(nullable)]
+    pub input_file: Option<String>,
+    /// read output from the file instead of stdout
+    #[sea_orm(nullable)]
+    pub output_file: Option<String>,
+    /// language of the checker, kept along with the problem package
+    #[sea_orm(nullable)]
+    pub checker_lang: Option<String>,
+    #[sea_orm(column_type = "Blob", nullable)]
+    pub checker: Option<Vec<u8>>,
 }
 
 #[derive(DerivePartialModel, FromQueryResult)]
@@ -83,6 +94,10 @@ impl IdModel {
             update_at: Default::default(),
             match_rule: Default::default(),
             order: Default::default(),
+            input_file: Default::default(),
+            output_file: Default::default(),
+            checker_lang: Default::default(),
+            checker: Default::default(),
         }
     }
 }
@@ -0,0 +1,204 @@
+//! snapshot of problem on every update
+//!
+//! Submission reference the revision it's judged against.
+use sea_orm::{ActiveValue, ConnectionTrait, QueryOrder, QuerySelect};
+
+use super::*;
+
+/// give up line diff if `lines of old * lines of new` exceed it
+const MAX_DIFF_CELL: usize = 4 * 1024 * 1024;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "problem_revision")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub problem_id: i32,
+    /// starts from 1, increase on every update of the problem
+    pub revision: u32,
+    /// editor
+    pub user_id: Option<i32>,
+    pub title: String,
+    pub content: String,
+    pub time: i64,
+    pub memory: i64,
+    pub match_rule: i32,
+    pub input_file: Option<String>,
+    pub output_file: Option<String>,
+    #[sea_orm(column_type = "Time")]
+    pub create_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::problem::Entity",
+        from = "Column::ProblemId",
+        to = "super::problem::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Problem,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    User,
+}
+
+impl Related<problem::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Problem.def()
+    }
+}
+
+impl Related<user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    /// write the revision back to problem
+    pub fn restore(&self, problem: &mut problem::ActiveModel) {
+        problem.title = ActiveValue::Set(self.title.clone());
+        problem.content = ActiveValue::Set(self.content.clone());
+        problem.time = ActiveValue::Set(self.time);
+        problem.memory = ActiveValue::Set(self.memory);
+        problem.match_rule = ActiveValue::Set(self.match_rule);
+        problem.input_file = ActiveValue::Set(self.input_file.clone());
+        problem.output_file = ActiveValue::Set(self.output_file.clone());
+    }
+}
+
+/// record current state of the problem as a new revision
+///
+/// should be called in the same transaction as the update
+pub async fn snapshot<C: ConnectionTrait>(
+    problem: &problem::Model,
+    user_id: i32,
+    db: &C,
+) -> Result<Model, Error> {
+    let last: Option<u32> = Entity::find()
+        .filter(Column::ProblemId.eq(problem.id))
+        .order_by_desc(Column::Revision)
+        .select_only()
+        .column(Column::Revision)
+        .into_tuple()
+        .one(db)
+        .await?;
+
+    Ok(ActiveModel {
+        problem_id: ActiveValue::Set(problem.id),
+        revision: ActiveValue::Set(last.map_or(1, |x| x.saturating_add(1))),
+        user_id: ActiveValue::Set(Some(user_id)),
+        title: ActiveValue::Set(problem.title.clone()),
+        content: ActiveValue::Set(problem.content.clone()),
+        time: ActiveValue::Set(problem.time),
+        memory: ActiveValue::Set(problem.memory),
+        match_rule: ActiveValue::Set(problem.match_rule),
+        input_file: ActiveValue::Set(problem.input_file.clone()),
+        output_file: ActiveValue::Set(problem.output_file.clone()),
+        ..Default::default()
+    }
+    .insert(db)
+    .await?)
+}
+
+/// change of a field other than content
+pub struct Change {
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+/// changed fields from `old` to `new`, content is diffed by [`diff_lines`]
+pub fn diff_fields(old: &Model, new: &Model) -> Vec<Change> {
+    fn opt(x: &Option<String>) -> String {
+        x.clone().unwrap_or_default()
+    }
+    [
+        ("title", old.title.clone(), new.title.clone()),
+        ("time", old.time.to_string(), new.time.to_string()),
+        ("memory", old.memory.to_string(), new.memory.to_string()),
+        (
+            "match_rule",
+            old.match_rule.to_string(),
+            new.match_rule.to_string(),
+        ),
+        ("input_file", opt(&old.input_file), opt(&new.input_file)),
+        ("output_file", opt(&old.output_file), opt(&new.output_file)),
+    ]
+    .into_iter()
+    .filter(|(_, old, new)| old != new)
+    .map(|(field, old, new)| Change { field, old, new })
+    .collect()
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Line<'a> {
+    Equal(&'a str),
+    Insert(&'a str),
+    Delete(&'a str),
+}
+
+/// line diff by longest common subsequence
+///
+/// Changed part is replaced as a whole if it's too large to compute.
+pub fn diff_lines<'a>(old: &'a str, new: &'a str) -> Vec<Line<'a>> {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut result: Vec<Line> = old[..prefix].iter().map(|x| Line::Equal(*x)).collect();
+    if a.len().saturating_mul(b.len()) > MAX_DIFF_CELL {
+        result.extend(a.iter().map(|x| Line::Delete(*x)));
+        result.extend(b.iter().map(|x| Line::Insert(*x)));
+    } else {
+        // lcs[i][j] is length of LCS of a[i..] and b[j..]
+        let width = b.len() + 1;
+        let mut lcs = vec![0_u32; (a.len() + 1) * width];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i * width + j] = match a[i] == b[j] {
+                    true => lcs[(i + 1) * width + j + 1] + 1,
+                    false => lcs[(i + 1) * width + j].max(lcs[i * width + j + 1]),
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() && j < b.len() {
+            if a[i] == b[j] {
+                result.push(Line::Equal(a[i]));
+                i += 1;
+                j += 1;
+            } else if lcs[(i + 1) * width + j] >= lcs[i * width + j + 1] {
+                result.push(Line::Delete(a[i]));
+                i += 1;
+            } else {
+                result.push(Line::Insert(b[j]));
+                j += 1;
+            }
+        }
+        result.extend(a[i..].iter().map(|x| Line::Delete(*x)));
+        result.extend(b[j..].iter().map(|x| Line::Insert(*x)));
+    }
+    result.extend(old[old.len() - suffix..].iter().map(|x| Line::Equal(*x)));
+    result
+}
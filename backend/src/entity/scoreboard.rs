@@ -0,0 +1,625 @@
+//! ranking of contest
+//!
+//! It's not backed by a table, rows are computed from `submit` table
+//! on every fetch.
+use std::{cmp::Reverse, collections::HashMap};
+
+use chrono::{Local, NaiveDateTime};
+use grpc::backend::ContestRule;
+use sea_orm::{QueryOrder, QuerySelect};
+use tracing::instrument;
+
+use self::util::paginator::Remain;
+use crate::util::code::Code;
+
+use super::*;
+
+/// penalty of each rejected submission before the accepted one(ICPC)
+const PENALTY_PER_ATTEMPT: i64 = 20 * 60;
+
+/// a submission within contest time
+struct Attempt {
+    user_id: i32,
+    problem_id: i32,
+    /// seconds from begin of contest(or virtual participation)
+    second: i64,
+    accept: bool,
+    score: u32,
+    /// made after scoreboard freeze
+    pending: bool,
+    /// compile error or system error, which is not counted as attempt
+    ignored: bool,
+}
+
+/// status of a participant on a problem
+#[derive(Clone, Debug)]
+pub struct Cell {
+    pub problem_id: i32,
+    /// submissions until first accepted one(all submissions in IOI rule)
+    pub attempt: u32,
+    /// seconds from begin of contest to first accepted submission
+    pub accept_at: Option<i64>,
+    /// best score
+    pub score: u32,
+    /// submissions after scoreboard freeze
+    pub pending: u32,
+}
+
+impl Cell {
+    fn new(problem_id: i32) -> Self {
+        Self {
+            problem_id,
+            attempt: 0,
+            accept_at: None,
+            score: 0,
+            pending: 0,
+        }
+    }
+    fn push(&mut self, rule: ContestRule, attempt: &Attempt) {
+        if rule == ContestRule::Icpc && self.accept_at.is_some() {
+            return;
+        }
+        // verdict is hidden after freeze, so ignored one is still pending
+        if attempt.pending {
+            self.pending += 1;
+            return;
+        }
+        if attempt.ignored {
+            return;
+        }
+        self.attempt += 1;
+        if attempt.accept && self.accept_at.is_none() {
+            self.accept_at = Some(attempt.second);
+        }
+        if rule == ContestRule::Ioi || attempt.accept {
+            self.score = self.score.max(attempt.score);
+        }
+    }
+    fn penalty(&self) -> i64 {
+        match self.accept_at {
+            Some(x) => x + PENALTY_PER_ATTEMPT * (self.attempt as i64 - 1),
+            None => 0,
+        }
+    }
+}
+
+/// a solo participant or a team
+struct Participant {
+    /// smallest user id among members
+    user_id: i32,
+    /// username, or team name
+    name: String,
+    team_id: Option<i32>,
+    members: Vec<i32>,
+    virtual_begin: Option<NaiveDateTime>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Row {
+    pub rank: u64,
+    /// smallest user id among members for team
+    pub user_id: i32,
+    /// username, or team name
+    pub username: String,
+    pub team_id: Option<i32>,
+    pub members: Vec<i32>,
+    /// set for virtual participant
+    pub virtual_begin: Option<NaiveDateTime>,
+    pub solved: u32,
+    /// in seconds, always zero in IOI rule
+    pub penalty: i64,
+    pub score: u32,
+    pub cells: Vec<Cell>,
+}
+
+impl Row {
+    /// key to rank, rows with the same key share the same rank
+    fn key(&self, rule: ContestRule) -> (Reverse<u32>, i64) {
+        match rule {
+            ContestRule::Icpc => (Reverse(self.solved), self.penalty),
+            ContestRule::Ioi => (Reverse(self.score), 0),
+        }
+    }
+}
+
+/// rank participants, tie is broken by user id
+fn rank(
+    rule: ContestRule,
+    problems: &[i32],
+    participants: Vec<Participant>,
+    attempts: impl Iterator<Item = Attempt>,
+) -> Vec<Row> {
+    let problem_idx: HashMap<i32, usize> =
+        problems.iter().enumerate().map(|(i, x)| (*x, i)).collect();
+    let user_idx: HashMap<i32, usize> = participants
+        .iter()
+        .enumerate()
+        .flat_map(|(i, x)| x.members.iter().map(move |x| (*x, i)))
+        .collect();
+
+    let mut cells: Vec<Vec<Cell>> = participants
+        .iter()
+        .map(|_| problems.iter().map(|x| Cell::new(*x)).collect())
+        .collect();
+    for attempt in attempts {
+        if let (Some(user), Some(problem)) = (
+            user_idx.get(&attempt.user_id),
+            problem_idx.get(&attempt.problem_id),
+        ) {
+            cells[*user][*problem].push(rule, &attempt);
+        }
+    }
+
+    let mut rows: Vec<Row> = participants
+        .into_iter()
+        .zip(cells)
+        .map(|(participant, cells)| Row {
+            rank: 0,
+            user_id: participant.user_id,
+            username: participant.name,
+            team_id: participant.team_id,
+            members: participant.members,
+            virtual_begin: participant.virtual_begin,
+            solved: cells.iter().filter(|x| x.accept_at.is_some()).count() as u32,
+            penalty: match rule {
+                ContestRule::Icpc => cells.iter().map(Cell::penalty).sum(),
+                ContestRule::Ioi => 0,
+            },
+            score: cells.iter().map(|x| x.score).sum(),
+            cells,
+        })
+        .collect();
+
+    rows.sort_by(|a, b| {
+        a.key(rule)
+            .cmp(&b.key(rule))
+            .then(a.user_id.cmp(&b.user_id))
+    });
+    for i in 0..rows.len() {
+        rows[i].rank = match i > 0 && rows[i].key(rule) == rows[i - 1].key(rule) {
+            true => rows[i - 1].rank,
+            false => i as u64 + 1,
+        };
+    }
+    rows
+}
+
+/// compute ranking of a contest
+///
+/// With `include_virtual`, virtual participants are ranked along with real
+/// ones. Submissions are timed from start of their participant, and the
+/// scoreboard is cut at elapsed time of viewer's ongoing virtual participation.
+#[instrument(skip(auth, db), level = "debug")]
+pub async fn board(
+    contest_id: i32,
+    include_virtual: bool,
+    auth: &Auth,
+    db: &DatabaseConnection,
+) -> Result<Vec<Row>, Error> {
+    let contest = contest::Entity::read_by_id(contest_id, auth)?
+        .one(db)
+        .await?
+        .ok_or(Error::NotInDB)?;
+    let freeze = contest.freeze_for(auth);
+    compute(&contest, include_virtual, freeze, auth.user_id(), db).await
+}
+
+/// compute ranking of a contest without checking permission
+///
+/// `freeze` is when the scoreboard is frozen for viewer, and `viewer` is
+/// used to cut the scoreboard, see [`board`].
+pub async fn compute(
+    contest: &contest::Model,
+    include_virtual: bool,
+    freeze: Option<NaiveDateTime>,
+    viewer: Option<i32>,
+    db: &DatabaseConnection,
+) -> Result<Vec<Row>, Error> {
+    let rule = ContestRule::try_from(contest.rule).unwrap_or_default();
+    let begin = contest.begin.unwrap_or(contest.create_at);
+    let duration = contest.end.map(|x| (x - begin).num_seconds());
+    let freeze = freeze.map(|x| (x - begin).num_seconds());
+
+    let problems: Vec<i32> = problem::Entity::find()
+        .filter(problem::Column::ContestId.eq(contest.id))
+        .order_by_asc(problem::Column::Order)
+        .order_by_asc(problem::Column::Id)
+        .select_only()
+        .column(problem::Column::Id)
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    let mut query = user::Entity::find()
+        .inner_join(user_contest::Entity)
+        .filter(user_contest::Column::ContestId.eq(contest.id))
+        .filter(user_contest::Column::Banned.eq(false));
+    if !include_virtual {
+        query = query.filter(user_contest::Column::VirtualBegin.is_null());
+    }
+    let users: Vec<(i32, String, Option<i32>, Option<NaiveDateTime>)> = query
+        .order_by_asc(user::Column::Id)
+        .select_only()
+        .columns([user::Column::Id, user::Column::Username])
+        .columns([
+            user_contest::Column::TeamId,
+            user_contest::Column::VirtualBegin,
+        ])
+        .into_tuple()
+        .all(db)
+        .await?;
+    let teams: HashMap<i32, String> = team::Entity::find()
+        .filter(team::Column::ContestId.eq(contest.id))
+        .select_only()
+        .columns([team::Column::Id, team::Column::Name])
+        .into_tuple::<(i32, String)>()
+        .all(db)
+        .await?
+        .into_iter()
+        .collect();
+
+    let starts: HashMap<i32, NaiveDateTime> = users
+        .iter()
+        .map(|(user_id, _, _, virtual_begin)| (*user_id, virtual_begin.unwrap_or(begin)))
+        .collect();
+    let now = Local::now().naive_local();
+    let cutoff = users
+        .iter()
+        .find(|x| Some(x.0) == viewer)
+        .and_then(|x| x.3)
+        .map(|x| (now - x).num_seconds())
+        .filter(|x| duration.map_or(true, |duration| *x < duration));
+    let participants = group(users, teams);
+
+    let mut query = submit::Entity::find()
+        .filter(submit::Column::ProblemId.is_in(problems.clone()))
+        .filter(submit::Column::Committed.eq(true))
+        .filter(submit::Column::UploadAt.gte(begin));
+    if let (Some(end), false) = (contest.end, include_virtual) {
+        query = query.filter(submit::Column::UploadAt.lte(end));
+    }
+    let submits: Vec<(Option<i32>, i32, NaiveDateTime, bool, u32, Option<u32>)> = query
+        .order_by_asc(submit::Column::UploadAt)
+        .order_by_asc(submit::Column::Id)
+        .select_only()
+        .columns([
+            submit::Column::UserId,
+            submit::Column::ProblemId,
+            submit::Column::UploadAt,
+            submit::Column::Accept,
+            submit::Column::Score,
+            submit::Column::Status,
+        ])
+        .into_tuple()
+        .all(db)
+        .await?;
+
+    let attempts = submits.into_iter().filter_map(
+        |(user_id, problem_id, upload_at, accept, score, status)| {
+            let user_id = user_id?;
+            let second = (upload_at - *starts.get(&user_id)?).num_seconds();
+            if second < 0
+                || duration.is_some_and(|x| second > x)
+                || cutoff.is_some_and(|x| second > x)
+            {
+                return None;
+            }
+            Some(Attempt {
+                user_id,
+                problem_id,
+                second,
+                accept,
+                score,
+                pending: freeze.is_some_and(|x| second >= x),
+                ignored: matches!(
+                    status.and_then(|x| Code::try_from(x).ok()),
+                    Some(Code::CompileError | Code::SystemError)
+                ),
+            })
+        },
+    );
+    Ok(rank(rule, &problems, participants, attempts))
+}
+
+/// merge members of the same team into one participant
+///
+/// `users` should be sorted by user id
+fn group(
+    users: Vec<(i32, String, Option<i32>, Option<NaiveDateTime>)>,
+    teams: HashMap<i32, String>,
+) -> Vec<Participant> {
+    let mut participants: Vec<Participant> = Vec::new();
+    let mut team_idx: HashMap<i32, usize> = HashMap::new();
+    for (user_id, username, team_id, virtual_begin) in users {
+        let team = team_id.and_then(|x| teams.get(&x).map(|name| (x, name)));
+        match team {
+            Some((team_id, name)) => match team_idx.get(&team_id) {
+                Some(idx) => participants[*idx].members.push(user_id),
+                None => {
+                    team_idx.insert(team_id, participants.len());
+                    participants.push(Participant {
+                        user_id,
+                        name: name.clone(),
+                        team_id: Some(team_id),
+                        members: vec![user_id],
+                        virtual_begin,
+                    });
+                }
+            },
+            None => participants.push(Participant {
+                user_id,
+                name: username,
+                team_id: None,
+                members: vec![user_id],
+                virtual_begin,
+            }),
+        }
+    }
+    participants
+}
+
+/// ScoreboardPaginator (offset base)
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct ScoreboardPaginator {
+    pub offset: u64,
+    pub contest_id: i32,
+    pub include_virtual: bool,
+    pub start_from_end: bool,
+    /// row count of the board computed by last fetch, so [`Remain`] of the
+    /// same request doesn't compute it again
+    #[serde(skip)]
+    pub total: Option<u64>,
+}
+
+pub struct ScoreboardSource;
+
+impl PagerData for ScoreboardSource {
+    /// contest id, and whether to include virtual participants
+    type Data = (i32, bool);
+}
+
+/// take rows in `start..end`, counting from the end if `rev`
+fn slice(mut rows: Vec<Row>, rev: bool, start: u64, end: u64) -> Vec<Row> {
+    if rev {
+        rows.reverse();
+    }
+    let end = (end as usize).min(rows.len());
+    let start = (start as usize).min(end);
+    rows.drain(start..end).collect()
+}
+
+#[async_trait]
+impl PaginateRaw for ScoreboardPaginator {
+    type Source = ScoreboardSource;
+    type Reflect = Row;
+
+    async fn fetch(
+        &mut self,
+        auth: &Auth,
+        size: i64,
+        offset: u64,
+        db: &DatabaseConnection,
+    ) -> Result<Vec<Self::Reflect>, Error> {
+        let rows = board(self.contest_id, self.include_virtual, auth, db).await?;
+        self.total = Some(rows.len() as u64);
+        let (start, end) = match size.is_negative() {
+            false => {
+                let start = self.offset + offset;
+                (start, start + size.unsigned_abs())
+            }
+            true => {
+                let end = self.offset.saturating_sub(offset);
+                (end.saturating_sub(size.unsigned_abs()), end)
+            }
+        };
+
+        let mut result = slice(rows, self.start_from_end, start, end);
+        match size.is_negative() {
+            false => self.offset = start + result.len() as u64,
+            true => {
+                self.offset = start;
+                result.reverse();
+            }
+        }
+        Ok(result)
+    }
+    async fn new_fetch(
+        data: <Self::Source as PagerData>::Data,
+        auth: &Auth,
+        size: u64,
+        offset: u64,
+        abs_dir: bool,
+        db: &DatabaseConnection,
+    ) -> Result<(Self, Vec<Self::Reflect>), Error> {
+        let rows = board(data.0, data.1, auth, db).await?;
+        let total = rows.len() as u64;
+        let result = slice(rows, abs_dir, offset, offset + size);
+
+        Ok((
+            ScoreboardPaginator {
+                offset: offset + result.len() as u64,
+                contest_id: data.0,
+                include_virtual: data.1,
+                start_from_end: abs_dir,
+                total: Some(total),
+            },
+            result,
+        ))
+    }
+}
+
+#[async_trait]
+impl Remain for ScoreboardPaginator {
+    async fn remain(&self, auth: &Auth, db: &DatabaseConnection) -> Result<u64, Error> {
+        let total = match self.total {
+            Some(x) => x,
+            None => board(self.contest_id, self.include_virtual, auth, db)
+                .await?
+                .len() as u64,
+        };
+        Ok(total.saturating_sub(self.offset))
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct Paginator(UninitPaginator<ScoreboardPaginator>);
+
+impl WithAuthTrait for Paginator {}
+
+impl Paginator {
+    pub fn new(contest_id: i32, include_virtual: bool) -> Self {
+        Self(UninitPaginator::new((contest_id, include_virtual), false))
+    }
+}
+
+impl<'a, 'b> WithDB<'a, WithAuth<'b, Paginator>> {
+    pub async fn fetch(&mut self, size: u64, offset: i64) -> Result<Vec<Row>, Error> {
+        let db = self.0;
+        let auth = self.1 .0;
+        self.1 .1 .0.fetch(size, offset, auth, db).await
+    }
+    pub async fn remain(&self) -> Result<u64, Error> {
+        let db = self.0;
+        let auth = self.1 .0;
+        self.1 .1 .0.remain(auth, db).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn solo(user_id: i32) -> Participant {
+        Participant {
+            user_id,
+            name: user_id.to_string(),
+            team_id: None,
+            members: vec![user_id],
+            virtual_begin: None,
+        }
+    }
+    fn attempt(user_id: i32, problem_id: i32, second: i64, accept: bool, score: u32) -> Attempt {
+        Attempt {
+            user_id,
+            problem_id,
+            second,
+            accept,
+            score,
+            pending: false,
+            ignored: false,
+        }
+    }
+
+    #[test]
+    fn icpc_penalty() {
+        let attempts = vec![
+            attempt(1, 10, 100, false, 0),
+            Attempt {
+                ignored: true,
+                ..attempt(1, 10, 150, false, 0)
+            },
+            attempt(1, 10, 200, true, 100),
+            // after accepted
+            attempt(1, 10, 300, false, 0),
+            attempt(1, 11, 400, false, 0),
+        ];
+        let rows = rank(
+            ContestRule::Icpc,
+            &[10, 11],
+            vec![solo(1)],
+            attempts.into_iter(),
+        );
+        let row = &rows[0];
+        assert_eq!(row.solved, 1);
+        assert_eq!(row.penalty, 200 + PENALTY_PER_ATTEMPT);
+        assert_eq!(row.cells[0].attempt, 2);
+        assert_eq!(row.cells[1].attempt, 1);
+        assert_eq!(row.cells[1].penalty(), 0);
+    }
+    #[test]
+    fn ioi_best_score() {
+        let attempts = vec![
+            attempt(1, 10, 100, false, 30),
+            attempt(1, 10, 200, false, 70),
+            attempt(1, 10, 300, false, 50),
+            attempt(1, 11, 400, true, 100),
+        ];
+        let rows = rank(
+            ContestRule::Ioi,
+            &[10, 11],
+            vec![solo(1)],
+            attempts.into_iter(),
+        );
+        let row = &rows[0];
+        assert_eq!(row.cells[0].score, 70);
+        assert_eq!(row.cells[0].attempt, 3);
+        assert_eq!(row.score, 170);
+        assert_eq!(row.penalty, 0);
+    }
+    #[test]
+    fn tie_share_rank() {
+        let attempts = vec![
+            attempt(3, 10, 100, true, 100),
+            attempt(2, 10, 100, true, 100),
+            attempt(1, 10, 50, false, 0),
+        ];
+        let rows = rank(
+            ContestRule::Icpc,
+            &[10],
+            vec![solo(1), solo(2), solo(3)],
+            attempts.into_iter(),
+        );
+        let ranks: Vec<_> = rows.iter().map(|x| (x.user_id, x.rank)).collect();
+        assert_eq!(ranks, vec![(2, 1), (3, 1), (1, 3)]);
+    }
+    #[test]
+    fn freeze_pending() {
+        let attempts = vec![
+            attempt(1, 10, 100, false, 0),
+            Attempt {
+                pending: true,
+                ..attempt(1, 10, 200, true, 100)
+            },
+            Attempt {
+                pending: true,
+                ignored: true,
+                ..attempt(1, 10, 300, false, 0)
+            },
+        ];
+        let rows = rank(
+            ContestRule::Icpc,
+            &[10],
+            vec![solo(1)],
+            attempts.into_iter(),
+        );
+        let row = &rows[0];
+        assert_eq!(row.solved, 0);
+        assert_eq!(row.cells[0].attempt, 1);
+        assert_eq!(row.cells[0].pending, 2);
+    }
+    #[test]
+    fn team_grouping() {
+        let users = vec![
+            (1, "a".to_string(), Some(7), None),
+            (2, "b".to_string(), None, None),
+            (3, "c".to_string(), Some(7), None),
+        ];
+        let teams = HashMap::from([(7, "team".to_string())]);
+        let participants = group(users, teams);
+        assert_eq!(participants.len(), 2);
+        assert_eq!(participants[0].name, "team");
+        assert_eq!(participants[0].members, vec![1, 3]);
+
+        let attempts = vec![
+            attempt(3, 10, 100, false, 0),
+            attempt(1, 10, 200, true, 100),
+            attempt(2, 10, 300, true, 100),
+        ];
+        let rows = rank(ContestRule::Icpc, &[10], participants, attempts.into_iter());
+        assert_eq!(rows[0].user_id, 2);
+        assert_eq!(rows[1].user_id, 1);
+        assert_eq!(rows[1].team_id, Some(7));
+        assert_eq!(rows[1].penalty, 200 + PENALTY_PER_ATTEMPT);
+        assert_eq!(rows[1].rank, 2);
+    }
+}
@@ -1,3 +1,7 @@
+use sea_orm::{
+    sea_query::{Expr, Query, SelectStatement},
+    Condition,
+};
 use tracing::instrument;
 
 use super::*;
@@ -27,6 +31,18 @@ pub struct Model {
     pub accept: bool,
     pub score: u32,
     pub public: bool,
+    /// stderr of the first failed testcase
+    #[sea_orm(column_type = "Blob", nullable)]
+    pub stderr: Option<Vec<u8>>,
+    /// exit code of the first failed testcase
+    #[sea_orm(nullable)]
+    pub exit_code: Option<i32>,
+    /// terminating signal of the first failed testcase
+    #[sea_orm(nullable)]
+    pub signal: Option<i32>,
+    /// revision of problem it's judged against
+    #[sea_orm(nullable)]
+    pub revision_id: Option<i32>,
 }
 
 #[derive(DerivePartialModel, FromQueryResult)]
@@ -52,6 +68,12 @@ pub struct PartialModel {
     pub accept: bool,
     pub score: u32,
     pub public: bool,
+    #[sea_orm(nullable)]
+    pub exit_code: Option<i32>,
+    #[sea_orm(nullable)]
+    pub signal: Option<i32>,
+    #[sea_orm(nullable)]
+    pub revision_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -88,10 +110,62 @@ impl Related<user::Entity> for Entity {
 
 impl ActiveModelBehavior for ActiveModel {}
 
+/// hide submissions made after scoreboard freeze
+fn freeze_filter<S: QueryFilter + Send>(query: S, auth: &Auth) -> S {
+    match auth.perm() {
+        RoleLv::Admin | RoleLv::Root => query,
+        _ => query.filter(Column::Id.not_in_subquery(contest::frozen_submit(auth))),
+    }
+}
+
+/// problems the user can write, either as problem owner or contest host
+fn writable_problem(user_id: i32) -> SelectStatement {
+    Query::select()
+        .column((problem::Entity, problem::Column::Id))
+        .from(problem::Entity)
+        .left_join(
+            contest::Entity,
+            Expr::col((contest::Entity, contest::Column::Id))
+                .equals((problem::Entity, problem::Column::ContestId)),
+        )
+        .cond_where(
+            Condition::any()
+                .add(Expr::col((problem::Entity, problem::Column::UserId)).eq(user_id))
+                .add(Expr::col((contest::Entity, contest::Column::Host)).eq(user_id)),
+        )
+        .to_owned()
+}
+
+/// submits whose state can be followed
+///
+/// besides readable submits, owner of the submit and writer of the problem
+/// can follow non-public submits
+pub fn follow_filter<S: QueryFilter + Send>(query: S, auth: &Auth) -> Result<S, Error> {
+    let user_id = match auth.user_id() {
+        Some(user_id) => user_id,
+        None => return Entity::read_filter(query, auth),
+    };
+    let perm = auth.perm();
+    if matches!(perm, RoleLv::Admin | RoleLv::Root) {
+        return Ok(query);
+    }
+    let mut cond = Condition::any()
+        .add(
+            Condition::all()
+                .add(Column::Public.eq(true))
+                .add(Column::Id.not_in_subquery(contest::frozen_submit(auth))),
+        )
+        .add(Column::UserId.eq(user_id));
+    if perm != RoleLv::User {
+        cond = cond.add(Column::ProblemId.in_subquery(writable_problem(user_id)));
+    }
+    Ok(query.filter(cond))
+}
+
 impl Filter for Entity {
     #[instrument(skip_all, level = "debug")]
-    fn read_filter<S: QueryFilter + Send>(query: S, _: &Auth) -> Result<S, Error> {
-        Ok(query.filter(Column::Public.eq(true)))
+    fn read_filter<S: QueryFilter + Send>(query: S, auth: &Auth) -> Result<S, Error> {
+        Ok(freeze_filter(query.filter(Column::Public.eq(true)), auth))
     }
 
     #[instrument(skip_all, level = "debug")]
@@ -135,7 +209,7 @@ impl Source for ParentPagerTrait {
     ) -> Result<Select<Self::Entity>, Error> {
         let parent: problem::IdModel =
             problem::Entity::related_read_by_id(auth, data.0, db).await?;
-        Ok(parent.upgrade().find_related(Entity))
+        Ok(freeze_filter(parent.upgrade().find_related(Entity), auth))
     }
 }
 
@@ -226,3 +300,120 @@ impl<'a, 'b> WithDB<'a, WithAuth<'b, Paginator>> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use sea_orm::{ActiveValue::NotSet, ConnectionTrait, Database, DbBackend, Schema, Set};
+
+    async fn setup() -> DatabaseConnection {
+        let db = Database::connect("sqlite::memory:").await.unwrap();
+        db.execute_unprepared("PRAGMA foreign_keys = OFF")
+            .await
+            .unwrap();
+        let schema = Schema::new(DbBackend::Sqlite);
+        for stmt in [
+            schema.create_table_from_entity(contest::Entity),
+            schema.create_table_from_entity(problem::Entity),
+            schema.create_table_from_entity(Entity),
+        ] {
+            db.execute(db.get_database_backend().build(&stmt))
+                .await
+                .unwrap();
+        }
+        let now = chrono::Local::now().naive_local();
+        contest::ActiveModel {
+            id: Set(1),
+            host: Set(1),
+            begin: Set(None),
+            end: Set(None),
+            title: Set("contest".to_string()),
+            content: Set(String::new()),
+            tags: Set(String::new()),
+            password: Set(None),
+            create_at: Set(now),
+            update_at: Set(now),
+            public: Set(false),
+            rule: Set(0),
+            freeze_at: Set(None),
+            register_begin: Set(None),
+            register_end: Set(None),
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        problem::ActiveModel {
+            id: Set(1),
+            user_id: Set(1),
+            contest_id: Set(Some(1)),
+            accept_count: Set(0),
+            submit_count: Set(0),
+            ac_rate: Set(0.0),
+            memory: Set(0),
+            time: Set(0),
+            difficulty: Set(0),
+            public: Set(false),
+            title: Set("problem".to_string()),
+            content: Set(String::new()),
+            create_at: Set(now),
+            update_at: Set(now),
+            match_rule: Set(0),
+            order: Set(0.0),
+            input_file: Set(None),
+            output_file: Set(None),
+            checker_lang: Set(None),
+            checker: Set(None),
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        ActiveModel {
+            id: Set(1),
+            user_id: Set(Some(2)),
+            problem_id: Set(1),
+            upload_at: Set(now),
+            time: NotSet,
+            accuracy: NotSet,
+            committed: Set(false),
+            lang: Set(String::new()),
+            code: Set(Vec::new()),
+            memory: NotSet,
+            pass_case: Set(0),
+            status: NotSet,
+            accept: Set(false),
+            score: Set(0),
+            public: Set(false),
+            stderr: NotSet,
+            exit_code: NotSet,
+            signal: NotSet,
+            revision_id: NotSet,
+        }
+        .insert(&db)
+        .await
+        .unwrap();
+        db
+    }
+    async fn can_follow(db: &DatabaseConnection, auth: Auth) -> bool {
+        follow_filter(Entity::find_by_id(1), &auth)
+            .unwrap()
+            .one(db)
+            .await
+            .unwrap()
+            .is_some()
+    }
+
+    #[tokio::test]
+    async fn participant_follow_own_submit() {
+        let db = setup().await;
+        assert!(can_follow(&db, Auth::User((2, RoleLv::User))).await);
+        assert!(!can_follow(&db, Auth::User((3, RoleLv::User))).await);
+        assert!(!can_follow(&db, Auth::Guest).await);
+    }
+    #[tokio::test]
+    async fn writer_follow_submit() {
+        let db = setup().await;
+        assert!(can_follow(&db, Auth::User((1, RoleLv::Super))).await);
+        assert!(!can_follow(&db, Auth::User((3, RoleLv::Super))).await);
+        assert!(can_follow(&db, Auth::User((3, RoleLv::Admin))).await);
+    }
+}
@@ -0,0 +1,43 @@
+//! team of a contest, members share a scoreboard row
+
+use super::*;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "team")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub contest_id: i32,
+    pub name: String,
+    #[sea_orm(column_type = "Time")]
+    pub create_at: chrono::NaiveDateTime,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::contest::Entity",
+        from = "Column::ContestId",
+        to = "super::contest::Column::Id",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    Contest,
+    #[sea_orm(has_many = "super::user_contest::Entity")]
+    UserContest,
+}
+
+impl Related<contest::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Contest.def()
+    }
+}
+
+impl Related<user_contest::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UserContest.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -276,6 +276,7 @@ impl PaginateRaw for ParentPaginator {
 
         let result = user_contest::Entity::find()
             .filter(user_contest::Column::ContestId.eq(self.ppk))
+            .filter(user_contest::Column::VirtualBegin.is_null())
             .order_by(
                 user_contest::Column::Score,
                 to_order(self.start_from_end ^ dir),
@@ -312,6 +313,7 @@ impl PaginateRaw for ParentPaginator {
 
         let result = user_contest::Entity::find()
             .filter(user_contest::Column::ContestId.eq(data.0))
+            .filter(user_contest::Column::VirtualBegin.is_null())
             .order_by(user_contest::Column::Score, to_order(abs_dir))
             .offset(offset)
             .limit(size)
@@ -348,6 +350,7 @@ impl Remain for ParentPaginator {
 
         let result = user_contest::Entity::find()
             .filter(user_contest::Column::ContestId.eq(self.ppk))
+            .filter(user_contest::Column::VirtualBegin.is_null())
             .order_by(user_contest::Column::Score, to_order(self.start_from_end))
             .count(db)
             .await?;
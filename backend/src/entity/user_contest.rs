@@ -9,6 +9,13 @@ pub struct Model {
     pub contest_id: i32,
     pub user_id: i32,
     pub score: u32,
+    /// members of the same team share a scoreboard row
+    pub team_id: Option<i32>,
+    /// kicked by host, and can't join again
+    pub banned: bool,
+    /// start time of virtual participation, which replays an ended contest
+    #[sea_orm(column_type = "Time", nullable)]
+    pub virtual_begin: Option<chrono::NaiveDateTime>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -29,6 +36,14 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     User,
+    #[sea_orm(
+        belongs_to = "super::team::Entity",
+        from = "Column::TeamId",
+        to = "super::team::Column::Id",
+        on_update = "NoAction",
+        on_delete = "SetNull"
+    )]
+    Team,
 }
 
 impl Related<contest::Entity> for Entity {
@@ -43,4 +58,10 @@ impl Related<user::Entity> for Entity {
     }
 }
 
+impl Related<team::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Team.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
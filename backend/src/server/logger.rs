@@ -8,6 +8,7 @@ use opentelemetry_sdk::{
         reader::{DefaultAggregationSelector, DefaultTemporalitySelector},
         PeriodicReader, SdkMeterProvider,
     },
+    propagation::TraceContextPropagator,
     runtime,
     trace::{BatchConfig, RandomIdGenerator, Sampler, Tracer},
 };
@@ -18,12 +19,13 @@ use opentelemetry_semantic_conventions::{
 use std::future::Future;
 use tracing::Level;
 use tracing_opentelemetry::{MetricsLayer, OpenTelemetryLayer};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{
+    filter::LevelFilter, layer::SubscriberExt, util::SubscriberInitExt, Layer,
+};
 
+use super::metrics::{PACKAGE_NAME, REGISTRY};
 use crate::config::CONFIG;
 
-static PACKAGE_NAME: &str = "mdoj-backend";
-
 fn resource() -> Resource {
     Resource::from_schema_url(
         [
@@ -39,15 +41,21 @@ fn resource() -> Resource {
 }
 
 // Construct MeterProvider for MetricsLayer
-fn init_meter_provider(reader: impl MetricReader) -> SdkMeterProvider {
+//
+// metrics are always exported to prometheus registry, in addition to `reader`
+fn init_meter_provider(reader: impl MetricReader) -> super::Result<SdkMeterProvider> {
+    let exporter = opentelemetry_prometheus::exporter()
+        .with_registry(REGISTRY.clone())
+        .build()?;
     let meter_provider = SdkMeterProvider::builder()
         .with_resource(resource())
         .with_reader(reader)
+        .with_reader(exporter)
         .build();
 
     global::set_meter_provider(meter_provider.clone());
 
-    meter_provider
+    Ok(meter_provider)
 }
 
 // Construct Tracer for OpenTelemetryLayer
@@ -76,6 +84,8 @@ fn init_tracer(endpoint: &str) -> super::Result<Tracer> {
 
 // Initialize tracing-subscriber and return OtelGuard for opentelemetry-related termination processing
 fn init_tracing_subscriber(level: Level, opentelemetry: Option<&str>) -> super::Result<OtelGuard> {
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
     let meter_provider = init_meter_provider(match opentelemetry {
         Some(_) => {
             let exporter = opentelemetry_otlp::new_exporter()
@@ -94,21 +104,21 @@ fn init_tracing_subscriber(level: Level, opentelemetry: Option<&str>) -> super::
             runtime::Tokio,
         )
         .build(),
-    });
-
-    match opentelemetry {
-        Some(endpoint) => tracing_subscriber::registry()
-            .with(tracing_subscriber::filter::LevelFilter::from_level(level))
-            .with(tracing_subscriber::fmt::layer())
-            .with(MetricsLayer::new(meter_provider.clone()))
-            .with(OpenTelemetryLayer::new(init_tracer(endpoint)?))
-            .init(),
-        None => tracing_subscriber::registry()
-            .with(tracing_subscriber::filter::LevelFilter::from_level(level))
-            .with(tracing_subscriber::fmt::layer())
-            .with(MetricsLayer::new(meter_provider.clone()))
-            .init(),
+    })?;
+
+    let tracer = match opentelemetry {
+        Some(endpoint) => Some(OpenTelemetryLayer::new(init_tracer(endpoint)?)),
+        None => None,
     };
+    // log level only applies to log and trace, metrics are always recorded
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .and_then(tracer)
+                .with_filter(LevelFilter::from_level(level)),
+        )
+        .with(MetricsLayer::new(meter_provider.clone()).with_filter(LevelFilter::DEBUG))
+        .init();
 
     Ok(OtelGuard { meter_provider })
 }
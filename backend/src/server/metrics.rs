@@ -0,0 +1,63 @@
+//! prometheus-style metrics endpoint
+//!
+//! Metrics are emitted as tracing events(see [`tracing_opentelemetry::MetricsLayer`]),
+//! except gauges, which are observed from [`meter`].
+use std::{convert::Infallible, net::SocketAddr};
+
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, StatusCode,
+};
+use opentelemetry::{global, metrics::Meter};
+use prometheus::{Encoder, Registry, TextEncoder};
+
+pub static PACKAGE_NAME: &str = "mdoj-backend";
+
+lazy_static::lazy_static! {
+    /// registry for prometheus exporter
+    pub static ref REGISTRY: Registry = Registry::new();
+}
+
+pub fn meter() -> Meter {
+    global::meter(PACKAGE_NAME)
+}
+
+async fn handle(req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    if req.uri().path() != "/metrics" {
+        return Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .unwrap());
+    }
+    let encoder = TextEncoder::new();
+    let mut buf = Vec::new();
+    if let Err(err) = encoder.encode(&REGISTRY.gather(), &mut buf) {
+        tracing::warn!("fail to encode metrics: {}", err);
+        return Ok(Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::empty())
+            .unwrap());
+    }
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, encoder.format_type())
+        .body(Body::from(buf))
+        .unwrap())
+}
+
+/// serve `/metrics` on `addr`
+pub async fn serve(addr: SocketAddr) {
+    let service = make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(handle)) });
+    // metrics is optional, don't take down the service if port is taken
+    let server = match hyper::Server::try_bind(&addr) {
+        Ok(x) => x,
+        Err(err) => {
+            tracing::error!("fail to serve metrics on {}: {}", addr, err);
+            return;
+        }
+    };
+    tracing::info!("serving metrics on http://{}/metrics", addr);
+    if let Err(err) = server.serve(service).await {
+        tracing::error!("metrics server exit: {}", err);
+    }
+}
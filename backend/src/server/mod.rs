@@ -1,6 +1,7 @@
 pub mod db;
 pub mod error;
 pub mod logger;
+pub mod metrics;
 
 pub use error::InitError;
 pub type Result<T> = std::result::Result<T, InitError>;
@@ -14,7 +15,8 @@ use crate::controller::*;
 use crate::config::CONFIG;
 use grpc::backend::{
     announcement_server::AnnouncementServer, chat_server::ChatServer,
-    contest_server::ContestServer, education_server::EducationServer,
+    clarification_server::ClarificationServer, contest_server::ContestServer,
+    education_server::EducationServer, playground_server::PlaygroundServer,
     problem_server::ProblemServer, submit_server::SubmitServer, testcase_server::TestcaseServer,
     token_server::TokenServer, user_server::UserServer,
 };
@@ -46,9 +48,11 @@ const DEFAULT_ALLOW_HEADERS: [&str; 5] = [
 pub struct Server {
     pub token: Arc<token::TokenController>,
     pub judger: Arc<judger::Judger>,
+    pub clarification: clarification::ClarificationController,
     pub crypto: crypto::CryptoController,
     pub imgur: imgur::ImgurController,
     pub rate_limit: rate_limit::RateLimitController,
+    pub upload: upload::UploadController,
     pub db: Arc<DatabaseConnection>,
     pub identity: Mutex<Option<Identity>>,
 }
@@ -105,15 +109,21 @@ impl Server {
                     .await
                     .unwrap(),
             ),
+            clarification: clarification::ClarificationController::new(),
             crypto,
             imgur: imgur::ImgurController::new(),
             rate_limit: rate_limit::RateLimitController::new(&CONFIG.grpc.trust_host),
+            upload: upload::UploadController::new(),
             identity: Mutex::new(identity),
             db,
         }))
     }
     /// Start the server
     pub async fn start(self: Arc<Self>) {
+        if let Some(addr) = CONFIG.metrics {
+            tokio::spawn(metrics::serve(addr));
+        }
+
         let self_ = ArcServer(self);
         let cors = CorsLayer::new()
             .allow_headers(
@@ -143,7 +153,11 @@ impl Server {
         server
             .layer(cors)
             .layer(GrpcWebLayer::new())
-            .add_service(ProblemServer::new(self_.clone()))
+            .add_service(
+                ProblemServer::new(self_.clone())
+                    .max_decoding_message_size(MAX_TESTCASE_CODEX_SIZE)
+                    .max_encoding_message_size(MAX_TESTCASE_CODEX_SIZE),
+            )
             .add_service(EducationServer::new(self_.clone()))
             .add_service(UserServer::new(self_.clone()))
             .add_service(TokenServer::new(self_.clone()))
@@ -154,8 +168,10 @@ impl Server {
                     .max_encoding_message_size(MAX_TESTCASE_CODEX_SIZE),
             )
             .add_service(SubmitServer::new(self_.clone()))
+            .add_service(PlaygroundServer::new(self_.clone()))
             .add_service(ChatServer::new(self_.clone()))
             .add_service(AnnouncementServer::new(self_.clone()))
+            .add_service(ClarificationServer::new(self_.clone()))
             .serve_with_shutdown(CONFIG.address.clone().parse().unwrap(), async {
                 if tokio::signal::ctrl_c().await.is_err() {
                     tracing::warn!("graceful_shutdown");
@@ -0,0 +1,381 @@
+//! testcases from archive of `Testcase.UploadArchive`
+//!
+//! Only `manifest.toml` and files ending with `.in`, `.out` or `.ans` are
+//! read, other files are skipped.
+//!
+//! Without `manifest.toml`, `x.in` is paired with `x.out` (or `x.ans`),
+//! ordered by path with numbers compared by value, and score is split evenly.
+//!
+//! ```toml
+//! [[testcase]]
+//! input = "1.in"
+//! output = "1.out"
+//! score = 10
+//! ```
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    io::{Cursor, Read},
+};
+
+use serde::Deserialize;
+
+/// the same as limit of `CreateTestcaseRequest`
+const MAX_FILE_SIZE: u64 = 16 * 1024 * 1024;
+/// max number of entries, including skipped ones
+const MAX_ENTRY: usize = 4096;
+/// max total size of files read from archive
+const MAX_TOTAL_SIZE: u64 = 512 * 1024 * 1024;
+const MANIFEST: &str = "manifest.toml";
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("zip error: `{0}`")]
+    Zip(#[from] zip::result::ZipError),
+    #[error("io error: `{0}`")]
+    Io(#[from] std::io::Error),
+    #[error("too many entries")]
+    TooManyEntry,
+    #[error("total size of files is too large")]
+    TooLarge,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Format {
+    Zip,
+    Tar,
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    testcase: Vec<ManifestCase>,
+}
+
+#[derive(Deserialize)]
+struct ManifestCase {
+    input: String,
+    output: String,
+    score: u32,
+}
+
+pub struct Case {
+    /// path of input file, used to report progress
+    pub name: String,
+    pub input: Vec<u8>,
+    pub output: Vec<u8>,
+    pub score: u32,
+}
+
+/// testcases in order and rejected files with reason
+#[derive(Default)]
+pub struct Plan {
+    pub cases: Vec<Case>,
+    pub rejected: Vec<(String, &'static str)>,
+}
+
+type Files = BTreeMap<String, Result<Vec<u8>, &'static str>>;
+
+/// whether the file is read from archive
+fn wanted(name: &str) -> bool {
+    name == MANIFEST || [".in", ".out", ".ans"].iter().any(|x| name.ends_with(x))
+}
+
+/// read regular files from archive, oversized files are kept as error
+fn read(format: Format, archive: &[u8]) -> Result<Files, Error> {
+    let mut files = Files::new();
+    let mut total = 0;
+    match format {
+        Format::Zip => {
+            let mut zip = zip::ZipArchive::new(Cursor::new(archive))?;
+            if zip.len() > MAX_ENTRY {
+                return Err(Error::TooManyEntry);
+            }
+            for i in 0..zip.len() {
+                let file = zip.by_index(i)?;
+                if file.is_dir() || !wanted(file.name()) {
+                    continue;
+                }
+                let name = file.name().to_string();
+                files.insert(name, read_limited(file, &mut total)?);
+            }
+        }
+        Format::Tar => {
+            let mut tar = tar::Archive::new(Cursor::new(archive));
+            for (i, entry) in tar.entries()?.enumerate() {
+                if i >= MAX_ENTRY {
+                    return Err(Error::TooManyEntry);
+                }
+                let entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let name = entry.path()?.to_string_lossy().into_owned();
+                if !wanted(&name) {
+                    continue;
+                }
+                files.insert(name, read_limited(entry, &mut total)?);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// don't trust size in header, the content may be larger
+///
+/// `total` is size of files read so far
+fn read_limited(file: impl Read, total: &mut u64) -> Result<Result<Vec<u8>, &'static str>, Error> {
+    let limit = MAX_FILE_SIZE.min(MAX_TOTAL_SIZE - *total);
+    let mut buf = Vec::new();
+    file.take(limit + 1).read_to_end(&mut buf)?;
+    *total += buf.len() as u64;
+    if *total > MAX_TOTAL_SIZE {
+        return Err(Error::TooLarge);
+    }
+    if buf.len() as u64 > MAX_FILE_SIZE {
+        return Ok(Err("file too large"));
+    }
+    Ok(Ok(buf))
+}
+
+/// parse archive and pair input with output
+pub fn plan(format: Format, archive: &[u8], total_score: u32) -> Result<Plan, Error> {
+    let mut files = read(format, archive)?;
+    let mut plan = Plan::default();
+
+    let pairs = match files.remove(MANIFEST) {
+        Some(manifest) => {
+            let manifest = manifest
+                .ok()
+                .and_then(|x| String::from_utf8(x).ok())
+                .and_then(|x| toml::from_str::<Manifest>(&x).ok());
+            let Some(manifest) = manifest else {
+                plan.rejected
+                    .push((MANIFEST.to_string(), "invalid manifest"));
+                return Ok(plan);
+            };
+            manifest
+                .testcase
+                .into_iter()
+                .map(|x| (x.input, x.output, Some(x.score)))
+                .collect()
+        }
+        None => convention(&files, &mut plan.rejected),
+    };
+
+    let count = pairs.iter().filter(|x| x.2.is_none()).count() as u32;
+    let mut index = 0;
+    for (input, output, score) in pairs {
+        let score = score.unwrap_or_else(|| {
+            index += 1;
+            total_score / count + u32::from(index <= total_score % count)
+        });
+        let (input_file, output_file) = (files.remove(&input), files.remove(&output));
+        match (input_file, output_file) {
+            (Some(Ok(input_file)), Some(Ok(output_file))) => plan.cases.push(Case {
+                name: input,
+                input: input_file,
+                output: output_file,
+                score,
+            }),
+            (None, _) => plan.rejected.push((input, "file not found")),
+            (_, None) => plan.rejected.push((output, "file not found")),
+            (Some(Err(err)), _) => plan.rejected.push((input, err)),
+            (_, Some(Err(err))) => plan.rejected.push((output, err)),
+        }
+    }
+    Ok(plan)
+}
+
+fn convention(
+    files: &Files,
+    rejected: &mut Vec<(String, &'static str)>,
+) -> Vec<(String, String, Option<u32>)> {
+    let mut pairs = Vec::new();
+    for name in files.keys() {
+        if let Some(stem) = name.strip_suffix(".in") {
+            match [".out", ".ans"]
+                .iter()
+                .map(|ext| format!("{stem}{ext}"))
+                .find(|x| files.contains_key(x))
+            {
+                Some(output) => pairs.push((name.clone(), output, None)),
+                None => rejected.push((name.clone(), "missing output")),
+            }
+        } else if let Some(stem) = name
+            .strip_suffix(".out")
+            .or_else(|| name.strip_suffix(".ans"))
+        {
+            if !files.contains_key(&format!("{stem}.in")) {
+                rejected.push((name.clone(), "missing input"));
+            }
+        }
+    }
+    pairs.sort_by(|a, b| natural_cmp(&a.0, &b.0));
+    pairs
+}
+
+/// compare names with runs of digits compared by value
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(x), Some(y)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+        if x.is_ascii_digit() && y.is_ascii_digit() {
+            let (na, ra) = split_digits(a);
+            let (nb, rb) = split_digits(b);
+            let (na, nb) = (na.trim_start_matches('0'), nb.trim_start_matches('0'));
+            let ord = na.len().cmp(&nb.len()).then_with(|| na.cmp(nb));
+            if ord != Ordering::Equal {
+                return ord;
+            }
+            (a, b) = (ra, rb);
+        } else {
+            if x != y {
+                return x.cmp(&y);
+            }
+            (a, b) = (&a[x.len_utf8()..], &b[y.len_utf8()..]);
+        }
+    }
+}
+
+fn split_digits(s: &str) -> (&str, &str) {
+    s.split_at(s.find(|x: char| !x.is_ascii_digit()).unwrap_or(s.len()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use super::*;
+
+    fn zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            zip.start_file(*name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+    fn tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut tar = tar::Builder::new(Vec::new());
+        for (name, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, *content).unwrap();
+        }
+        tar.into_inner().unwrap()
+    }
+
+    #[test]
+    fn natural_order() {
+        assert_eq!(natural_cmp("2.in", "10.in"), Ordering::Less);
+        assert_eq!(natural_cmp("a10b2", "a10b1"), Ordering::Greater);
+        assert_eq!(natural_cmp("a01", "a1"), Ordering::Equal);
+        assert_eq!(natural_cmp("a", "a1"), Ordering::Less);
+        assert_eq!(natural_cmp("b1", "a2"), Ordering::Greater);
+    }
+    #[test]
+    fn convention_pair() {
+        let files: Files = ["1.in", "1.out", "2.in", "2.ans", "3.in", "4.out"]
+            .into_iter()
+            .map(|x| (x.to_string(), Ok(Vec::new())))
+            .collect();
+        let mut rejected = Vec::new();
+        let pairs = convention(&files, &mut rejected);
+        assert_eq!(
+            pairs,
+            vec![
+                ("1.in".to_string(), "1.out".to_string(), None),
+                ("2.in".to_string(), "2.ans".to_string(), None),
+            ]
+        );
+        assert_eq!(
+            rejected,
+            vec![
+                ("3.in".to_string(), "missing output"),
+                ("4.out".to_string(), "missing input"),
+            ]
+        );
+    }
+    #[test]
+    fn plan_even_split() {
+        let archive = zip(&[
+            ("10.in", b"c"),
+            ("10.out", b"C"),
+            ("2.in", b"b"),
+            ("2.out", b"B"),
+            ("1.in", b"a"),
+            ("1.out", b"A"),
+            ("readme.md", b"skipped"),
+        ]);
+        let plan = plan(Format::Zip, &archive, 100).unwrap();
+        assert!(plan.rejected.is_empty());
+        let cases: Vec<_> = plan
+            .cases
+            .iter()
+            .map(|x| (x.name.as_str(), x.input.as_slice(), x.score))
+            .collect();
+        assert_eq!(
+            cases,
+            vec![
+                ("1.in", b"a".as_slice(), 34),
+                ("2.in", b"b".as_slice(), 33),
+                ("10.in", b"c".as_slice(), 33),
+            ]
+        );
+    }
+    #[test]
+    fn plan_manifest() {
+        let manifest = b"[[testcase]]\ninput = \"a.in\"\noutput = \"a.ans\"\nscore = 7\n";
+        let archive = tar(&[(MANIFEST, manifest), ("a.in", b"1 2"), ("a.ans", b"3")]);
+        let plan = plan(Format::Tar, &archive, 100).unwrap();
+        assert!(plan.rejected.is_empty());
+        assert_eq!(plan.cases.len(), 1);
+        assert_eq!(plan.cases[0].output, b"3");
+        assert_eq!(plan.cases[0].score, 7);
+    }
+    #[test]
+    fn plan_bad_manifest() {
+        let archive = tar(&[(MANIFEST, b"not toml"), ("1.in", b""), ("1.out", b"")]);
+        let plan = plan(Format::Tar, &archive, 100).unwrap();
+        assert!(plan.cases.is_empty());
+        assert_eq!(
+            plan.rejected,
+            vec![(MANIFEST.to_string(), "invalid manifest")]
+        );
+    }
+    #[test]
+    fn plan_missing_pair() {
+        let manifest = b"[[testcase]]\ninput = \"1.in\"\noutput = \"1.out\"\nscore = 10\n";
+        let archive = zip(&[(MANIFEST, manifest), ("1.in", b"")]);
+        let plan = plan(Format::Zip, &archive, 100).unwrap();
+        assert!(plan.cases.is_empty());
+        assert_eq!(plan.rejected, vec![("1.out".to_string(), "file not found")]);
+    }
+    #[test]
+    fn plan_oversized_file() {
+        let large = vec![0; MAX_FILE_SIZE as usize + 1];
+        let archive = tar(&[("1.in", large.as_slice()), ("1.out", b"")]);
+        let plan = plan(Format::Tar, &archive, 100).unwrap();
+        assert!(plan.cases.is_empty());
+        assert_eq!(plan.rejected, vec![("1.in".to_string(), "file too large")]);
+    }
+    #[test]
+    fn read_limit() {
+        let mut total = 0;
+        let file = read_limited(&b"hello"[..], &mut total).unwrap();
+        assert_eq!(file.unwrap(), b"hello");
+        assert_eq!(total, 5);
+
+        let file = read_limited(std::io::repeat(0).take(MAX_FILE_SIZE + 1), &mut total).unwrap();
+        assert_eq!(file.unwrap_err(), "file too large");
+
+        let mut total = MAX_TOTAL_SIZE - 1;
+        let err = read_limited(&b"ab"[..], &mut total).unwrap_err();
+        assert!(matches!(err, Error::TooLarge));
+    }
+}
@@ -17,6 +17,12 @@ pub trait BoundCheck {
     }
 }
 
+/// same as judger, only plain file name is allowed, empty name is treated as unset
+fn invalid_file_name(name: &Option<String>) -> bool {
+    name.as_ref()
+        .is_some_and(|x| x.len() > 128 || x == "." || x == ".." || x.contains(['/', '\0']))
+}
+
 macro_rules! impl_basic_bound_check {
     ($n:ident) => {
         paste::paste! {
@@ -80,6 +86,34 @@ list_paginator_request!(Contest);
 list_paginator_request!(Education);
 list_paginator_request!(User);
 
+impl BoundCheck for CreateTeamRequest {
+    fn check(&self) -> bool {
+        self.name.is_empty() || self.name.len() > 128
+    }
+}
+
+impl BoundCheck for JoinContestRequest {
+    fn check(&self) -> bool {
+        self.invite_code
+            .as_ref()
+            .map(String::len)
+            .unwrap_or_default()
+            > 64
+    }
+}
+
+impl BoundCheck for ScoreboardRequest {
+    fn check(&self) -> bool {
+        self.size == 0
+            || self.size >= i32::MAX as u64
+            || self.offset.unsigned_abs() >= i32::MAX as u64
+            || matches!(
+                &self.request,
+                Some(scoreboard_request::Request::Paginator(x)) if x.len() > 512
+            )
+    }
+}
+
 impl BoundCheck for ListProblemRequest {
     fn check(&self) -> bool {
         if self.size == 0
@@ -120,6 +154,18 @@ impl BoundCheck for ListTokenRequest {
     }
 }
 
+impl BoundCheck for ListClarificationRequest {
+    fn check(&self) -> bool {
+        self.size == 0
+            || self.size >= i32::MAX as u64
+            || self.offset.unsigned_abs() >= i32::MAX as u64
+            || matches!(
+                &self.request,
+                Some(list_clarification_request::Request::Paginator(x)) if x.len() > 512
+            )
+    }
+}
+
 impl BoundCheck for ListChatRequest {
     fn check(&self) -> bool {
         self.offset == 0 || self.offset.unsigned_abs() > 4096 || self.size > 128
@@ -160,6 +206,18 @@ impl BoundCheck for CreateChatRequest {
     }
 }
 
+impl BoundCheck for CreateClarificationRequest {
+    fn check(&self) -> bool {
+        self.question.len() > 8 * 1024
+    }
+}
+
+impl BoundCheck for AnswerClarificationRequest {
+    fn check(&self) -> bool {
+        self.answer.len() > 8 * 1024
+    }
+}
+
 impl BoundCheck for CreateContestRequest {
     fn check(&self) -> bool {
         self.info.title.len() > 128
@@ -201,12 +259,31 @@ impl BoundCheck for UpdateContestRequest {
 impl BoundCheck for CreateProblemRequest {
     fn check(&self) -> bool {
         self.info.title.len() > 128
+            || invalid_file_name(&self.info.input_file)
+            || invalid_file_name(&self.info.output_file)
             || self.info.tags.len() > 1024
             || self.info.content.len() > 128 * 1024
             || self.info.memory > 4 * 1024 * 1024 * 1024
             || self.info.time > 60 * 1000 * 1000
     }
 }
+impl BoundCheck for problem_package::Meta {
+    fn check(&self) -> bool {
+        self.title.len() > 128
+            || invalid_file_name(&self.input_file)
+            || invalid_file_name(&self.output_file)
+            || self.tags.len() > 1024
+            || self.content.len() > 128 * 1024
+            || self.memory > 4 * 1024 * 1024 * 1024
+            || self.time > 60 * 1000 * 1000
+            || self
+                .checker
+                .as_ref()
+                .map(|x| x.code.len())
+                .unwrap_or_default()
+                > 64 * 1024
+    }
+}
 impl BoundCheck for UpdateProblemRequest {
     fn check(&self) -> bool {
         self.info
@@ -215,6 +292,8 @@ impl BoundCheck for UpdateProblemRequest {
             .map(String::len)
             .unwrap_or_default()
             > 128
+            || invalid_file_name(&self.info.input_file)
+            || invalid_file_name(&self.info.output_file)
             || self.info.tags.iter().map(String::len).sum::<usize>() > 1024
             || self
                 .info
@@ -232,12 +311,36 @@ impl BoundCheck for CreateSubmitRequest {
     }
 }
 
+impl BoundCheck for PlaygroundRequest {
+    fn check(&self) -> bool {
+        self.code.len() > 64 * 1024 || self.input.len() > 64 * 1024
+    }
+}
+
+impl BoundCheck for SendStdinRequest {
+    fn check(&self) -> bool {
+        self.chunk.len() > 64 * 1024
+    }
+}
+
 impl BoundCheck for CreateTestcaseRequest {
     fn check(&self) -> bool {
         self.info.input.len() > 16 * 1024 * 1024 || self.info.output.len() > 16 * 1024 * 1024
     }
 }
 
+impl BoundCheck for UploadChunkRequest {
+    fn check(&self) -> bool {
+        self.chunk.len() > 16 * 1024 * 1024
+    }
+}
+
+impl BoundCheck for problem_package::Testcase {
+    fn check(&self) -> bool {
+        self.input.len() > 16 * 1024 * 1024 || self.output.len() > 16 * 1024 * 1024
+    }
+}
+
 impl BoundCheck for UpdateTestcaseRequest {
     fn check(&self) -> bool {
         self.info.input.as_ref().map(Vec::len).unwrap_or_default() > 256 * 1024
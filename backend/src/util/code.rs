@@ -15,6 +15,7 @@ pub enum Code {
     RestrictedFunction = 8,
     Unknown = 9,
     OutputLimitExceeded = 10,
+    RealTimeLimitExceeded = 11,
 }
 
 impl TryFrom<u32> for Code {
@@ -32,6 +33,7 @@ impl TryFrom<u32> for Code {
             8 => Ok(Code::RestrictedFunction),
             9 => Ok(Code::Unknown),
             10 => Ok(Code::OutputLimitExceeded),
+            11 => Ok(Code::RealTimeLimitExceeded),
             _ => Err(()),
         }
     }
@@ -46,10 +48,11 @@ impl From<Code> for JudgerCode {
             Code::MemoryLimitExceeded => JudgerCode::Mle,
             Code::RuntimeError => JudgerCode::Re,
             Code::CompileError => JudgerCode::Ce,
-            Code::SystemError => JudgerCode::Na,
+            Code::SystemError => JudgerCode::Se,
             Code::RestrictedFunction => JudgerCode::Rf,
             Code::Unknown => JudgerCode::Na,
             Code::OutputLimitExceeded => JudgerCode::Ole,
+            Code::RealTimeLimitExceeded => JudgerCode::Rtle,
         }
     }
 }
@@ -66,6 +69,8 @@ impl From<JudgerCode> for Code {
             JudgerCode::Tle => Code::TimeLimitExceeded,
             JudgerCode::Mle => Code::MemoryLimitExceeded,
             JudgerCode::Ole => Code::OutputLimitExceeded,
+            JudgerCode::Rtle => Code::RealTimeLimitExceeded,
+            JudgerCode::Se => Code::SystemError,
         }
     }
 }
@@ -79,10 +84,11 @@ impl From<Code> for BackendCode {
             Code::MemoryLimitExceeded => BackendCode::MemoryLimitExcess,
             Code::RuntimeError => BackendCode::RuntimeError,
             Code::CompileError => BackendCode::CompileError,
-            Code::SystemError => BackendCode::Unknown,
+            Code::SystemError => BackendCode::SystemError,
             Code::RestrictedFunction => BackendCode::RestrictedFunction,
             Code::Unknown => BackendCode::Unknown,
             Code::OutputLimitExceeded => BackendCode::OutputLimitExcess,
+            Code::RealTimeLimitExceeded => BackendCode::RealTimeLimitExcess,
         }
     }
 }
@@ -95,9 +101,11 @@ impl From<BackendCode> for Code {
             BackendCode::MemoryLimitExcess => Code::MemoryLimitExceeded,
             BackendCode::RuntimeError => Code::RuntimeError,
             BackendCode::CompileError => Code::CompileError,
-            BackendCode::Unknown => Code::SystemError,
+            BackendCode::Unknown => Code::Unknown,
             BackendCode::RestrictedFunction => Code::RestrictedFunction,
             BackendCode::OutputLimitExcess => Code::OutputLimitExceeded,
+            BackendCode::RealTimeLimitExcess => Code::RealTimeLimitExceeded,
+            BackendCode::SystemError => Code::SystemError,
         }
     }
 }
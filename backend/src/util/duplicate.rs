@@ -86,20 +86,26 @@ macro_rules! create_cache {
 
 create_cache!(PublishRequest, ());
 create_cache!(JoinContestRequest, ());
+create_cache!(StartVirtualRequest, ());
 create_cache!(RemoveRequest, ());
 create_cache!(RejudgeRequest, ());
 create_cache!(PublishContestRequest, ());
+create_cache!(RemoveParticipantRequest, ());
 
 create_cache!(RefreshRequest, TokenInfo, 8);
 create_cache!(LoginRequest, TokenInfo, 8);
 
 create_cache!(CreateAnnouncementRequest, Id);
 create_cache!(CreateChatRequest, Id);
+create_cache!(CreateClarificationRequest, Id);
 create_cache!(CreateContestRequest, Id);
 create_cache!(CreateEducationRequest, Id);
 create_cache!(CreateProblemRequest, Id);
+create_cache!(ImportProblemRequest, Id);
 create_cache!(CreateSubmitRequest, Id);
 create_cache!(CreateTestcaseRequest, Id);
+create_cache!(CreateTeamRequest, Id);
+create_cache!(CreateInviteRequest, Invite);
 create_cache!(CreateUserRequest, Id);
 
 create_cache!(UpdateAnnouncementRequest, ());
@@ -108,6 +114,8 @@ create_cache!(UpdateEducationRequest, ());
 create_cache!(UpdateProblemRequest, ());
 create_cache!(UpdateTestcaseRequest, ());
 create_cache!(UpdateUserRequest, ());
+create_cache!(AnswerClarificationRequest, ());
+create_cache!(RestoreRevisionRequest, ());
 create_cache!(UpdatePasswordRequest, ());
 
 create_cache!(AddAnnouncementToContestRequest, ());
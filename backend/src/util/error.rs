@@ -1,4 +1,4 @@
-use crate::controller::{imgur as image, judger, token};
+use crate::controller::{imgur as image, judger, token, upload};
 use crate::report_internal;
 use tonic::Status;
 
@@ -42,6 +42,8 @@ pub enum Error {
     Judger(#[from] judger::Error),
     #[error("token error: `{0}`")]
     Token(#[from] token::Error),
+    #[error("upload error: `{0}`")]
+    Upload(#[from] upload::Error),
     #[error("retry later")]
     Retry,
 }
@@ -100,6 +102,7 @@ impl From<Error> for Status {
             Error::Image(x) => report_internal!(error, "{}", x),
             Error::Judger(x) => x.into(),
             Error::Token(x) => x.into(),
+            Error::Upload(x) => x.into(),
             Error::Retry => Status::aborted("Should retry"),
         }
     }
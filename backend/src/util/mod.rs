@@ -1,3 +1,4 @@
+pub mod archive;
 pub mod auth;
 pub mod bound;
 pub mod code;
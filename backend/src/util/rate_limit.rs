@@ -103,6 +103,25 @@ impl RateLimit for ListUserRequest {
     }
 }
 
+// scoreboard is computed on every request
+impl RateLimit for ScoreboardRequest {
+    fn get_cost(&self) -> u32 {
+        self.size
+            .saturating_add(self.offset.unsigned_abs() / 7)
+            .saturating_add(20)
+            .min(u32::MAX as u64) as u32
+    }
+}
+
+impl RateLimit for ListClarificationRequest {
+    fn get_cost(&self) -> u32 {
+        self.size
+            .saturating_add(self.offset.unsigned_abs() / 8)
+            .saturating_add(3)
+            .min(u32::MAX as u64) as u32
+    }
+}
+
 impl RateLimit for ListChatRequest {
     fn get_cost(&self) -> u32 {
         self.size
@@ -147,17 +166,46 @@ impl RateLimit for CreateSubmitRequest {
         430
     }
 }
+impl RateLimit for PlaygroundRequest {
+    fn get_cost(&self) -> u32 {
+        430
+    }
+}
+impl RateLimit for CommitArchiveRequest {
+    fn get_cost(&self) -> u32 {
+        100
+    }
+}
+impl RateLimit for UploadChunkRequest {}
+impl RateLimit for ImportProblemRequest {
+    fn get_cost(&self) -> u32 {
+        430
+    }
+}
 impl RateLimit for CreateChatRequest {
     fn get_cost(&self) -> u32 {
         10
     }
 }
+impl RateLimit for CreateClarificationRequest {
+    fn get_cost(&self) -> u32 {
+        10
+    }
+}
+impl RateLimit for AnswerClarificationRequest {}
+impl RateLimit for RestoreRevisionRequest {}
+impl RateLimit for DiffRevisionRequest {}
 
 impl RateLimit for AddAnnouncementToContestRequest {}
 impl RateLimit for AddEducationToProblemRequest {}
 impl RateLimit for AddTestcaseToProblemRequest {}
 impl RateLimit for AddProblemToContestRequest {}
 impl RateLimit for JoinContestRequest {}
+impl RateLimit for StartVirtualRequest {}
+impl RateLimit for SendStdinRequest {}
+impl RateLimit for CreateTeamRequest {}
+impl RateLimit for CreateInviteRequest {}
+impl RateLimit for RemoveParticipantRequest {}
 impl RateLimit for RejudgeRequest {}
 impl RateLimit for LoginRequest {
     fn get_cost(&self) -> u32 {
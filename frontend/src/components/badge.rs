@@ -30,6 +30,8 @@ pub fn StateBadge(state: grpc::StateCode) -> impl IntoView {
         grpc::StateCode::TimeLimitExcess => ("border-red-500", "TLE"),
         grpc::StateCode::MemoryLimitExcess => ("border-red-500", "MLE"),
         grpc::StateCode::OutputLimitExcess => ("border-red-500", "OLE"),
+        grpc::StateCode::RealTimeLimitExcess => ("border-red-500", "RTLE"),
+        grpc::StateCode::SystemError => ("border-yellow-400", "SE"),
     };
     view! { <p class=tw_join!("p-1 m-1 w-min h-min border-2 rounded m-auto",style)>{display}</p> }
 }
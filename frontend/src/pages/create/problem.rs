@@ -126,6 +126,8 @@ pub fn Problem() -> impl IntoView {
             match_rule: match_rule().unwrap().into(),
             // TODO: remove this when new API is complete
             order: 0.0,
+            input_file: None,
+            output_file: None,
         };
         create.dispatch((info, token().unwrap()));
     };
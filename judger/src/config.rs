@@ -1,39 +1,82 @@
 use libc::getuid;
 use serde::{Deserialize, Serialize};
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
 
-#[cfg(not(test))]
-use std::path::{Path, PathBuf};
-use std::{net::SocketAddr, str::FromStr};
+use std::{
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
 
-#[cfg(not(test))]
-fn try_load_config(config_path: impl AsRef<Path>) -> Result<Config, Box<dyn std::error::Error>> {
-    use std::{fs::File, io::Read};
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// load config from `CONFIG_PATH`(default to `config.toml`)
+///
+/// Fall back to default config only when the file doesn't exist,
+/// any other error(permission, syntax, unknown field...) is returned.
+pub fn load_config() -> Result<Config> {
+    let path =
+        PathBuf::from_str(&std::env::var("CONFIG_PATH").unwrap_or("config.toml".to_string()))?;
+    try_load_config(path).map(Config::check)
+}
 
-    let mut file = File::open(config_path.as_ref())?;
-    let mut buf = String::new();
-    file.read_to_string(&mut buf)?;
+fn try_load_config(config_path: impl AsRef<Path>) -> Result<Config> {
+    let config_path = config_path.as_ref();
+    let buf = match std::fs::read_to_string(config_path) {
+        Ok(x) => x,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            tracing::warn!("{:?} not found, use default config", config_path);
+            return Ok(Config::default());
+        }
+        Err(err) => return Err(err.into()),
+    };
     let config = toml::from_str(buf.as_str())?;
-    log::info!("load config from {:?}", config_path.as_ref());
+    tracing::info!("load config from {:?}", config_path);
     Ok(config)
 }
 
 #[cfg(not(test))]
 lazy_static::lazy_static! {
-    pub static ref CONFIG: Config={
-        let path=PathBuf::from_str(
-            &std::env::var("CONFIG_PATH").unwrap_or("config.toml".to_string()))
-            .expect("Invalid CONFIG_PATH");
-        try_load_config(path).unwrap_or_default().check()
+    pub static ref CONFIG: ConfigCell = match load_config() {
+        Ok(config) => ConfigCell::new(config),
+        Err(err) => {
+            eprintln!("fail to load config: {}", err);
+            std::process::exit(1);
+        }
     };
 }
 
 #[cfg(test)]
 lazy_static::lazy_static! {
-    pub static ref CONFIG: Config=Config::default();
+    pub static ref CONFIG: ConfigCell = ConfigCell::new(Config::default());
+}
+
+/// holder of current config, which could be partially reloaded at runtime
+pub struct ConfigCell(RwLock<Arc<Config>>);
+
+impl ConfigCell {
+    fn new(config: Config) -> Self {
+        Self(RwLock::new(Arc::new(config)))
+    }
+    /// get a snapshot of current config
+    pub fn get(&self) -> Arc<Config> {
+        self.0.read().unwrap().clone()
+    }
+    /// reload config from file, return the old one
+    ///
+    /// Only fields that are safe to change at runtime are applied,
+    /// see [`Config::reloadable`].
+    pub fn reload(&self) -> Result<Arc<Config>> {
+        let new = load_config()?;
+        let mut current = self.0.write().unwrap();
+        let merged = current.reloadable(new);
+        Ok(std::mem::replace(&mut *current, Arc::new(merged)))
+    }
 }
 
 /// method to load cpu usage from control group
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub enum Accounting {
     #[default]
@@ -51,7 +94,7 @@ fn default_ratio_memory() -> f64 {
 }
 
 /// Ratio for resource multiplier
-#[derive(Serialize, Deserialize, Default)]
+#[derive(Serialize, Deserialize, Default, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct Ratio {
     #[serde(default = "default_ratio_cpu")]
@@ -60,6 +103,31 @@ pub struct Ratio {
     pub memory: f64,
 }
 
+/// TLS option for grpc server
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Tls {
+    /// certificate of judger in pem
+    pub cert: PathBuf,
+    /// private key of judger in pem
+    pub key: PathBuf,
+    /// require client(backend) to present certificate signed by this CA
+    #[serde(default)]
+    pub client_ca: Option<PathBuf>,
+}
+
+impl Tls {
+    pub fn load(&self) -> std::io::Result<ServerTlsConfig> {
+        let cert = std::fs::read(&self.cert)?;
+        let key = std::fs::read(&self.key)?;
+        let mut config = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+        if let Some(ca) = &self.client_ca {
+            config = config.client_ca_root(Certificate::from_pem(std::fs::read(ca)?));
+        }
+        Ok(config)
+    }
+}
+
 fn default_log() -> u8 {
     1
 }
@@ -79,7 +147,11 @@ fn default_addr() -> SocketAddr {
     SocketAddr::from_str("0.0.0.0:8081").unwrap()
 }
 
-#[derive(Serialize, Deserialize)]
+fn default_metrics() -> Option<SocketAddr> {
+    Some(SocketAddr::from_str("127.0.0.1:9092").unwrap())
+}
+
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
     #[serde(default)]
@@ -96,16 +168,47 @@ pub struct Config {
     pub memory: u64,
     #[serde(default = "default_addr")]
     pub address: SocketAddr,
+    /// endpoint of opentelemetry collector, disable exporting if not set
+    #[serde(default)]
+    pub opentelemetry: Option<String>,
+    /// address to serve prometheus-style `/metrics`, disable if not set
+    #[serde(default = "default_metrics")]
+    pub metrics: Option<SocketAddr>,
+    /// serve grpc over plaintext if not set
+    #[serde(default)]
+    pub tls: Option<Tls>,
 }
 
 impl Config {
     pub fn check(mut self) -> Self {
         if !self.rootless && unsafe { getuid() } != 0 {
             self.rootless = true;
-            log::warn!("rootles is not specified, but not running as root, set rootless=true");
+            tracing::warn!("rootles is not specified, but not running as root, set rootless=true");
         }
         self
     }
+    /// apply fields that are safe to change at runtime from `new`
+    ///
+    /// They are `ratio`, `log`, `secret` and `memory`, other fields require restart.
+    pub fn reloadable(&self, new: Config) -> Config {
+        let mut config = self.clone();
+        config.ratio = new.ratio.clone();
+        config.log = new.log;
+        config.secret = new.secret.clone();
+        config.memory = new.memory;
+        if toml::to_string(&config).ok() != toml::to_string(&new).ok() {
+            tracing::warn!("some changes require restart to take effect");
+        }
+        config
+    }
+    /// print config in toml, with secret redacted
+    pub fn to_redacted_string(&self) -> String {
+        let mut config = self.clone();
+        if config.secret.is_some() {
+            config.secret = Some("<redacted>".to_string());
+        }
+        toml::to_string_pretty(&config).unwrap()
+    }
 }
 
 impl Default for Config {
@@ -118,6 +221,9 @@ impl Default for Config {
             secret: None,
             memory: default_memory(),
             address: default_addr(),
+            opentelemetry: None,
+            metrics: default_metrics(),
+            tls: None,
         }
     }
 }
@@ -16,7 +16,7 @@ pub enum Error {
 
 impl From<Error> for Status {
     fn from(value: Error) -> Self {
-        log::error!("{:?}", value);
+        tracing::error!("{:?}", value);
         Status::internal("internal error: unknown")
     }
 }
@@ -29,6 +29,10 @@ pub enum ClientError {
     InvalidLanguageUuid,
     #[error("impossible memory requirement")]
     ImpossibleMemoryRequirement,
+    #[error("invalid file name")]
+    InvalidFileName,
+    #[error("first message of interactive execution should be start")]
+    MissingExecStart,
 }
 
 impl From<ClientError> for Status {
@@ -41,6 +45,10 @@ impl From<ClientError> for Status {
             ClientError::ImpossibleMemoryRequirement => {
                 Status::failed_precondition("Impossible memory requirement")
             }
+            ClientError::InvalidFileName => Status::invalid_argument("Invalid file name"),
+            ClientError::MissingExecStart => {
+                Status::invalid_argument("First message should be start")
+            }
         }
     }
 }
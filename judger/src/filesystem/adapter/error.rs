@@ -38,13 +38,13 @@ pub enum FuseError {
 impl From<FuseError> for fuse3::Errno {
     fn from(value: FuseError) -> Self {
         #[cfg(test)]
-        log::warn!("FUSE driver return result: {}", value);
+        tracing::warn!("FUSE driver return result: {}", value);
         match value {
             FuseError::IsDir => libc::EISDIR,
             FuseError::NotDir => libc::ENOTDIR,
             FuseError::Eof => libc::EOF,
             FuseError::OutOfPermit => {
-                log::info!("out of resource");
+                tracing::info!("out of resource");
                 libc::ENOMEM
             }
             FuseError::InvalidPath | FuseError::InvalidIno => libc::ENOENT,
@@ -52,7 +52,7 @@ impl From<FuseError> for fuse3::Errno {
             FuseError::InvalidArg => libc::EINVAL,
             FuseError::AlreadyExist => libc::EEXIST,
             err => {
-                log::warn!("FUSE driver broken: {}", err);
+                tracing::warn!("FUSE driver broken: {}", err);
                 libc::EINVAL
             }
         }
@@ -80,6 +80,7 @@ where
     async fn destroy(&self, _: Request) {}
 
     async fn lookup(&self, req: Request, parent: u64, name: &OsStr) -> FuseResult<ReplyEntry> {
+        crate::metrics::fuse_op("lookup");
         let tree = self.tree.lock();
         let parent_node = tree.get(parent as usize).ok_or(FuseError::InvalidIno)?;
         let node = parent_node
@@ -98,6 +99,7 @@ where
         _: Option<u64>,
         _: u32,
     ) -> FuseResult<ReplyAttr> {
+        crate::metrics::fuse_op("getattr");
         let tree = self.tree.lock();
         let node = tree.get(inode as usize).ok_or(FuseError::InvalidIno)?;
         // FIXME: unsure about the inode
@@ -110,11 +112,13 @@ where
         _: Option<u64>,
         _: SetAttr,
     ) -> FuseResult<ReplyAttr> {
+        crate::metrics::fuse_op("setattr");
         let tree = self.tree.lock();
         let node = tree.get(inode as usize).ok_or(FuseError::InvalidIno)?;
         Ok(reply_attr(&req, node.get_value(), inode))
     }
     async fn readlink(&self, _: Request, inode: Inode) -> FuseResult<ReplyData> {
+        crate::metrics::fuse_op("readlink");
         let tree = self.tree.lock();
         let node = tree.get(inode as usize).ok_or(FuseError::InvalidIno)?;
         let link = node
@@ -133,6 +137,7 @@ where
         _: u32,
         _: u32,
     ) -> FuseResult<ReplyEntry> {
+        crate::metrics::fuse_op("mkdir");
         let mut tree = self.tree.lock();
         let mut parent_node = tree.get_mut(parent as usize).ok_or(FuseError::InvalidIno)?;
         if parent_node.get_value().kind() != FileType::Directory {
@@ -145,6 +150,7 @@ where
         Ok(reply_entry(&req, node.get_value(), ino))
     }
     async fn unlink(&self, _: Request, parent: Inode, name: &OsStr) -> FuseResult<()> {
+        crate::metrics::fuse_op("unlink");
         let mut tree = self.tree.lock();
         let mut parent_node = tree.get_mut(parent as usize).ok_or(FuseError::InvalidIno)?;
         if parent_node.get_value().kind() != FileType::Directory {
@@ -154,6 +160,7 @@ where
         Ok(())
     }
     async fn open(&self, _: Request, inode: u64, flags: u32) -> FuseResult<ReplyOpen> {
+        crate::metrics::fuse_op("open");
         // ignore write permission, because some application may open files
         // with write permission but never write
         let tree = self.tree.lock();
@@ -174,6 +181,7 @@ where
         offset: u64,
         size: u32,
     ) -> FuseResult<ReplyData> {
+        crate::metrics::fuse_op("read");
         let session = self.handle_table.get(fh).ok_or(FuseError::HandleNotFound)?;
         let mut lock = session.lock().await;
 
@@ -196,6 +204,7 @@ where
         _: u32,
         _: u32,
     ) -> FuseResult<ReplyWrite> {
+        crate::metrics::fuse_op("write");
         let session = self
             .handle_table
             .get(fh)
@@ -249,6 +258,7 @@ where
         Ok(())
     }
     async fn opendir(&self, _: Request, inode: u64, flags: u32) -> FuseResult<ReplyOpen> {
+        crate::metrics::fuse_op("opendir");
         let tree = self.tree.lock();
         let node = tree.get(inode as usize).ok_or(FuseError::InvalidIno)?;
         if node.get_value().kind() != FileType::Directory {
@@ -267,6 +277,7 @@ where
         _: u64,
         offset: i64,
     ) -> FuseResult<ReplyDirectory<Self::DirEntryStream<'_>>> {
+        crate::metrics::fuse_op("readdir");
         let tree = self.tree.lock();
         let node = tree.get(parent as usize).ok_or(FuseError::InvalidIno)?;
 
@@ -323,6 +334,7 @@ where
         _: u32,
         flags: u32,
     ) -> FuseResult<ReplyCreated> {
+        crate::metrics::fuse_op("create");
         let mut tree = self.tree.lock();
         let mut parent_node = tree.get_mut(parent as usize).ok_or(FuseError::InvalidIno)?;
         if parent_node.get_value().kind() != FileType::Directory {
@@ -369,6 +381,7 @@ where
         offset: u64,
         _: u64,
     ) -> FuseResult<ReplyDirectoryPlus<Self::DirEntryPlusStream<'_>>> {
+        crate::metrics::fuse_op("readdirplus");
         let tree = self.tree.lock();
         let node = tree.get(parent as usize).ok_or(FuseError::InvalidIno)?;
 
@@ -430,16 +443,14 @@ mod test {
         raw::{Filesystem as _, Request},
         Errno,
     };
-    use tokio::fs::File;
-
-    use crate::filesystem::adapter::Template;
+    use crate::filesystem::{adapter::Template, entry::Rootfs};
 
     use super::Filesystem;
 
     #[allow(clippy::declare_interior_mutable_const)]
     const UNIQUE_COUNTER: AtomicU64 = AtomicU64::new(0);
 
-    async fn nested_tar() -> Filesystem<File> {
+    async fn nested_tar() -> Filesystem<Rootfs> {
         let template = Template::new("test/nested.tar").await.unwrap();
         template.as_filesystem(1024 * 1024)
     }
@@ -25,18 +25,18 @@ impl<E> HandleTable<E> {
         let handle = self
             .handle_generator
             .fetch_add(1, std::sync::atomic::Ordering::AcqRel);
-        log::trace!("allocate handle: {}", handle);
+        tracing::trace!("allocate handle: {}", handle);
         self.table.lock().insert(handle, Arc::new(entry));
         handle
     }
     /// Get an entry from the table
     pub fn get(&self, handle: FileHandle) -> Option<Arc<E>> {
-        log::trace!("get handle: {}", handle);
+        tracing::trace!("get handle: {}", handle);
         self.table.lock().get(&handle).cloned()
     }
     /// Remove an entry from the table
     pub fn remove(&self, handle: FileHandle) -> Option<Arc<E>> {
-        log::trace!("deallocate handle: {}", handle);
+        tracing::trace!("deallocate handle: {}", handle);
         self.table.lock().remove(&handle)
     }
 }
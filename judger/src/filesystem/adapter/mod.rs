@@ -11,18 +11,16 @@ pub use template::Template;
 mod test {
     use super::*;
     // use crate::semaphore::Semaphore;
-    use env_logger::*;
 
     #[tokio::test]
     #[ignore = "not meant to be tested"]
     async fn test_mount() {
-        Builder::from_default_env()
-            .filter_level(log::LevelFilter::Debug)
-            .filter_module("tracing::span::active", log::LevelFilter::Trace)
+        tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
             .try_init()
             .ok();
 
-        log::info!("mounting test tarball in .temp ...");
+        tracing::info!("mounting test tarball in .temp ...");
         let template = Template::new("plugins/rlua-54.lang").await.unwrap();
         let filesystem = template.as_filesystem(1024 * 1024 * 1024);
         let mut mount_handle = filesystem.raw_mount_with_path("./.temp/12").await.unwrap();
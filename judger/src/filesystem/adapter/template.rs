@@ -1,12 +1,9 @@
 use std::path::Path;
 
-use tokio::{
-    fs::File,
-    io::{AsyncRead, AsyncSeek},
-};
+use tokio::io::{AsyncRead, AsyncSeek};
 
 use crate::filesystem::{
-    entry::{Entry, EntryTree},
+    entry::{Entry, EntryTree, Rootfs},
     table::{to_internal_path, AdjTable},
 };
 
@@ -25,7 +22,7 @@ where
 {
     /// use template to create a filesystem
     pub fn as_filesystem(&self, permit: u64) -> Filesystem<F> {
-        log::debug!("create filesystem with permit: {}", permit);
+        tracing::debug!("create filesystem with permit: {}", permit);
         Filesystem::new(self.0.clone(), permit)
     }
     /// read a file by path
@@ -41,9 +38,9 @@ where
     }
 }
 
-impl Template<File> {
-    /// Create a new template from a tar file
-    pub async fn new(path: impl AsRef<Path> + Clone) -> std::io::Result<Self> {
+impl Template<Rootfs> {
+    /// Create a new template from a tar file(optionally in zstd seekable format)
+    pub async fn new(path: impl AsRef<Path>) -> std::io::Result<Self> {
         let tree = EntryTree::new(path).await?;
         Ok(Self(tree.0))
     }
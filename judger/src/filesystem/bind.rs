@@ -0,0 +1,135 @@
+//! Alternative to FUSE filesystem
+//!
+//! Rootfs of plugin is extracted on host once, and each sandbox get a
+//! size-limited tmpfs as writable root, with top-level directories of the
+//! extracted rootfs bind-mounted read-only on it by nsjail.
+//!
+//! Top-level files and symlinks(like `/lib -> usr/lib`) are copied to the
+//! tmpfs, so writes outside top-level directories are kept between stages
+//! just like FUSE filesystem.
+//!
+//! Mounting tmpfs requires `CAP_SYS_ADMIN`, so it's unavailable in rootless mode.
+use std::{
+    ffi::{CString, OsStr},
+    io,
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use super::{entry::Rootfs, mkdtemp::MkdTemp, MountHandle};
+
+fn to_cstring(s: &OsStr) -> io::Result<CString> {
+    CString::new(s.as_bytes()).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+}
+
+fn mount_tmpfs(target: &Path, size: u64) -> io::Result<()> {
+    let source = to_cstring(OsStr::new("tmpfs"))?;
+    let target = to_cstring(target.as_os_str())?;
+    let data = to_cstring(OsStr::new(&format!("size={},mode=0755", size)))?;
+    let ret = unsafe {
+        libc::mount(
+            source.as_ptr(),
+            target.as_ptr(),
+            source.as_ptr(),
+            libc::MS_NOSUID | libc::MS_NODEV,
+            data.as_ptr() as *const _,
+        )
+    };
+    match ret {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+pub(super) fn umount(target: &Path) -> io::Result<()> {
+    let target = to_cstring(target.as_os_str())?;
+    match unsafe { libc::umount2(target.as_ptr(), libc::MNT_DETACH) } {
+        0 => Ok(()),
+        _ => Err(io::Error::last_os_error()),
+    }
+}
+
+/// Rootfs extracted on host
+pub struct BindRoot {
+    lower: PathBuf,
+    /// top-level directories, as `(source, target)`
+    binds: Arc<[(PathBuf, PathBuf)]>,
+    /// top-level files and symlinks
+    files: Vec<PathBuf>,
+}
+
+impl Drop for BindRoot {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_dir_all(&self.lower) {
+            tracing::warn!("fail to remove extracted rootfs: {}", err);
+        }
+    }
+}
+
+impl BindRoot {
+    /// extract rootfs(tar file, optionally compressed) to a temporary directory
+    pub async fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        let (_, reader) = Rootfs::open(path).await?;
+        let lower = unsafe { MkdTemp::new_inner("/tmp/mdoj-rootfs-XXXXXX") };
+        let dst = lower.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut archive = tar::Archive::new(reader);
+            archive.set_preserve_permissions(true);
+            archive.set_preserve_ownerships(true);
+            archive.unpack(dst)
+        })
+        .await
+        .unwrap()?;
+        tracing::debug!("extract rootfs to {}", lower.display());
+
+        let (mut binds, mut files) = (Vec::new(), Vec::new());
+        let mut entries = tokio::fs::read_dir(&lower).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let name = PathBuf::from(entry.file_name());
+            match entry.file_type().await?.is_dir() {
+                true => binds.push((entry.path(), Path::new("/").join(name))),
+                false => files.push(name),
+            }
+        }
+        Ok(Self {
+            lower,
+            binds: binds.into(),
+            files,
+        })
+    }
+    /// mount a new tmpfs as writable root, with `content` written to `file`
+    ///
+    /// `size` is the size limit of tmpfs, which bound all writes in sandbox
+    pub async fn mount(
+        &self,
+        size: u64,
+        file: impl AsRef<Path>,
+        content: Vec<u8>,
+    ) -> io::Result<MountHandle> {
+        let mountpoint = MkdTemp::new();
+        let root = mountpoint.get_path().to_path_buf();
+        let target = root.clone();
+        tokio::task::spawn_blocking(move || mount_tmpfs(&target, size))
+            .await
+            .unwrap()?;
+        let handle = MountHandle::bind(mountpoint, self.binds.clone());
+
+        for (_, target) in self.binds.iter() {
+            tokio::fs::create_dir(root.join(target.strip_prefix("/").unwrap())).await?;
+        }
+        for name in &self.files {
+            let (src, dst) = (self.lower.join(name), root.join(name));
+            match tokio::fs::symlink_metadata(&src).await?.is_symlink() {
+                true => tokio::fs::symlink(tokio::fs::read_link(&src).await?, dst).await?,
+                false => {
+                    tokio::fs::copy(src, dst).await?;
+                }
+            }
+        }
+
+        let file = file.as_ref();
+        tokio::fs::write(root.join(file.strip_prefix("/").unwrap_or(file)), content).await?;
+        Ok(handle)
+    }
+}
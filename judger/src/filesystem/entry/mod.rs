@@ -1,6 +1,8 @@
 mod ro;
+mod rootfs;
 mod rw;
 mod tar;
+mod zstd;
 
 use self::{ro::TarBlock, rw::MemBlock};
 use bytes::Bytes;
@@ -16,6 +18,7 @@ use tokio::{
 
 use super::resource::Resource;
 
+pub use rootfs::Rootfs;
 pub use tar::EntryTree;
 pub const BLOCKSIZE: usize = 4096;
 const MAX_READ_BLK: usize = 1024;
@@ -38,7 +38,7 @@ where
     F: AsyncRead + AsyncSeek + Unpin + 'static,
 {
     pub fn new(file: Arc<Mutex<F>>, start: u64, size: u32) -> Self {
-        log::trace!("new block: start={}, size={}", start, size);
+        tracing::trace!("new block: start={}, size={}", start, size);
         Self {
             file,
             start,
@@ -95,7 +95,7 @@ where
         let mut buf = vec![0_u8; size];
 
         if let Err(err) = lock.read_exact(&mut buf).await {
-            log::warn!("tarball change at runtime, result in error: {}", err);
+            tracing::warn!("tarball change at runtime, result in error: {}", err);
         }
 
         Ok(bytes::Bytes::from(buf))
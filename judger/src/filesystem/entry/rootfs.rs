@@ -0,0 +1,77 @@
+use std::{
+    io::{self, Read, SeekFrom},
+    path::Path,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncReadExt, AsyncSeek, ReadBuf},
+};
+
+use super::zstd::{ZstdFile, ZSTD_MAGIC};
+
+/// backing file of plugin's rootfs
+///
+/// Plain tarball is read directly, and tarball in zstd seekable format
+/// is decompressed on demand.
+pub enum Rootfs {
+    Tar(File),
+    Zstd(ZstdFile),
+}
+
+impl Rootfs {
+    /// open rootfs, detect compression by magic number
+    ///
+    /// return the rootfs and a sequential reader of the decompressed tarball
+    pub async fn open(
+        path: impl AsRef<Path>,
+    ) -> io::Result<(Self, Box<dyn Read + Send + 'static>)> {
+        let mut file = File::open(path.as_ref()).await?;
+        let mut magic = [0_u8; 4];
+        let compressed = match file.read_exact(&mut magic).await {
+            Ok(_) => u32::from_le_bytes(magic) == ZSTD_MAGIC,
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => false,
+            Err(err) => return Err(err),
+        };
+
+        let std_file = File::open(path.as_ref()).await?.into_std().await;
+        if compressed {
+            let rootfs = Rootfs::Zstd(ZstdFile::new(file.into_std().await).await?);
+            let reader = zstd::stream::read::Decoder::new(std_file)?;
+            Ok((rootfs, Box::new(reader)))
+        } else {
+            Ok((Rootfs::Tar(file), Box::new(std_file)))
+        }
+    }
+}
+
+impl AsyncRead for Rootfs {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Rootfs::Tar(x) => Pin::new(x).poll_read(cx, buf),
+            Rootfs::Zstd(x) => Pin::new(x).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncSeek for Rootfs {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        match self.get_mut() {
+            Rootfs::Tar(x) => Pin::new(x).start_seek(position),
+            Rootfs::Zstd(x) => Pin::new(x).start_seek(position),
+        }
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        match self.get_mut() {
+            Rootfs::Tar(x) => Pin::new(x).poll_complete(cx),
+            Rootfs::Zstd(x) => Pin::new(x).poll_complete(cx),
+        }
+    }
+}
@@ -6,14 +6,13 @@ use tar::{Archive, EntryType};
 #[cfg(test)]
 use tokio::io::BufReader;
 use tokio::{
-    fs::File,
     io::{AsyncRead, AsyncSeek, Result},
     sync::Mutex,
 };
 
 use crate::filesystem::table::{to_internal_path, AdjTable};
 
-use super::{ro::TarBlock, Entry};
+use super::{ro::TarBlock, Entry, Rootfs};
 
 pub struct EntryTree<F>(pub AdjTable<Entry<F>>)
 where
@@ -60,7 +59,7 @@ where
             )),
             EntryType::Directory => Entry::Directory,
             x => {
-                log::warn!("unsupported entry type: {:?}", x);
+                tracing::warn!("unsupported entry type: {:?}", x);
                 return Ok(());
             }
         };
@@ -83,10 +82,9 @@ where
     }
 }
 
-impl EntryTree<File> {
-    pub async fn new(path: impl AsRef<Path> + Clone) -> Result<Self> {
-        let file = File::open(path.clone()).await?;
-        let std_file = File::open(path).await?.into_std().await;
+impl EntryTree<Rootfs> {
+    pub async fn new(path: impl AsRef<Path>) -> Result<Self> {
+        let (file, std_file) = Rootfs::open(path).await?;
         Self::inner_new(file, std_file).await
     }
 }
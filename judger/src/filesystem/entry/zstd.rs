@@ -0,0 +1,264 @@
+//! reader for tarball compressed in [zstd seekable format]
+//!
+//! The tarball is split into independent zstd frames, and a seek table
+//! (skippable frame at the end of file) record size of each frame,
+//! so we can decompress only the frames that are actually read.
+//!
+//! [zstd seekable format]: https://github.com/facebook/zstd/blob/dev/contrib/seekable_format/zstd_seekable_compression_format.md
+
+use std::{
+    future::Future,
+    io::{self, SeekFrom},
+    num::NonZeroUsize,
+    os::unix::fs::FileExt,
+    pin::Pin,
+    sync::Arc,
+    task::{ready, Context, Poll},
+};
+
+use lru::LruCache;
+use tokio::{
+    io::{AsyncRead, AsyncSeek, ReadBuf},
+    task::JoinHandle,
+};
+
+/// magic number of the first frame of a zstd stream
+pub const ZSTD_MAGIC: u32 = 0xFD2FB528;
+const SEEKABLE_MAGIC: u32 = 0x8F92EAB1;
+const SKIPPABLE_MAGIC: u32 = 0x184D2A5E;
+/// size of seek table footer
+const FOOTER_SIZE: u64 = 9;
+/// number of decompressed frames to cache
+const CACHE_FRAME: usize = 8;
+
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[derive(Debug, Clone)]
+struct Frame {
+    compressed_offset: u64,
+    compressed_size: u32,
+    offset: u64,
+    size: u32,
+}
+
+impl Frame {
+    fn decompress(&self, file: &std::fs::File) -> io::Result<Arc<Vec<u8>>> {
+        let mut buf = vec![0_u8; self.compressed_size as usize];
+        file.read_exact_at(&mut buf, self.compressed_offset)?;
+        let content = zstd::bulk::decompress(&buf, self.size as usize)?;
+        if content.len() != self.size as usize {
+            return Err(invalid("frame size mismatch with seek table"));
+        }
+        Ok(Arc::new(content))
+    }
+}
+
+/// seek table of the file
+#[derive(Debug)]
+struct Index {
+    frames: Vec<Frame>,
+    size: u64,
+}
+
+impl Index {
+    fn read_u32(file: &std::fs::File, offset: u64) -> io::Result<u32> {
+        let mut buf = [0_u8; 4];
+        file.read_exact_at(&mut buf, offset)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+    fn new(file: &std::fs::File) -> io::Result<Self> {
+        let len = file.metadata()?.len();
+        if len < FOOTER_SIZE + 8 {
+            return Err(invalid("file too small for seek table"));
+        }
+        let footer = len - FOOTER_SIZE;
+        if Self::read_u32(file, footer + 5)? != SEEKABLE_MAGIC {
+            return Err(invalid("seek table not found"));
+        }
+        let count = Self::read_u32(file, footer)? as u64;
+        let mut descriptor = [0_u8];
+        file.read_exact_at(&mut descriptor, footer + 4)?;
+        let entry_size = match descriptor[0] & 0x80 {
+            0 => 8,
+            _ => 12,
+        };
+
+        let table = footer
+            .checked_sub(count * entry_size)
+            .ok_or(invalid("seek table out of bound"))?;
+        if table < 8 || Self::read_u32(file, table - 8)? != SKIPPABLE_MAGIC {
+            return Err(invalid("seek table not found"));
+        }
+
+        let mut raw = vec![0_u8; (count * entry_size) as usize];
+        file.read_exact_at(&mut raw, table)?;
+
+        let mut frames = Vec::with_capacity(count as usize);
+        let (mut compressed_offset, mut offset) = (0, 0);
+        for entry in raw.chunks_exact(entry_size as usize) {
+            let compressed_size = u32::from_le_bytes(entry[0..4].try_into().unwrap());
+            let size = u32::from_le_bytes(entry[4..8].try_into().unwrap());
+            frames.push(Frame {
+                compressed_offset,
+                compressed_size,
+                offset,
+                size,
+            });
+            compressed_offset += compressed_size as u64;
+            offset += size as u64;
+        }
+        if compressed_offset > table - 8 {
+            return Err(invalid("seek table out of bound"));
+        }
+        Ok(Self {
+            frames,
+            size: offset,
+        })
+    }
+    /// find index of frame containing `offset`
+    fn find(&self, offset: u64) -> usize {
+        self.frames
+            .partition_point(|x| x.offset + x.size as u64 <= offset)
+    }
+}
+
+/// A zstd seekable compressed file, behave like decompressed file
+///
+/// Frames are decompressed in blocking thread,
+/// and recently used frames are cached.
+pub struct ZstdFile {
+    file: Arc<std::fs::File>,
+    index: Arc<Index>,
+    cache: LruCache<usize, Arc<Vec<u8>>>,
+    cursor: u64,
+    pending: Option<(usize, JoinHandle<io::Result<Arc<Vec<u8>>>>)>,
+}
+
+impl ZstdFile {
+    pub async fn new(file: std::fs::File) -> io::Result<Self> {
+        let file = Arc::new(file);
+        let file_ = file.clone();
+        let index = tokio::task::spawn_blocking(move || Index::new(&file_))
+            .await
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))??;
+        Ok(Self {
+            file,
+            index: Arc::new(index),
+            cache: LruCache::new(NonZeroUsize::new(CACHE_FRAME).unwrap()),
+            cursor: 0,
+            pending: None,
+        })
+    }
+}
+
+impl AsyncRead for ZstdFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let self_ = self.get_mut();
+        if self_.cursor >= self_.index.size || buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        let idx = self_.index.find(self_.cursor);
+        let content = match self_.cache.get(&idx) {
+            Some(x) => x.clone(),
+            None => {
+                if !matches!(&self_.pending, Some((x, _)) if *x == idx) {
+                    let file = self_.file.clone();
+                    let frame = self_.index.frames[idx].clone();
+                    self_.pending = Some((
+                        idx,
+                        tokio::task::spawn_blocking(move || frame.decompress(&file)),
+                    ));
+                }
+                let (_, handle) = self_.pending.as_mut().unwrap();
+                let result = ready!(Pin::new(handle).poll(cx));
+                self_.pending = None;
+                let content = result.map_err(|err| io::Error::new(io::ErrorKind::Other, err))??;
+                self_.cache.put(idx, content.clone());
+                content
+            }
+        };
+
+        let start = (self_.cursor - self_.index.frames[idx].offset) as usize;
+        let len = buf.remaining().min(content.len() - start);
+        buf.put_slice(&content[start..start + len]);
+        self_.cursor += len as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for ZstdFile {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let self_ = self.get_mut();
+        let cursor = match position {
+            SeekFrom::Start(x) => Some(x),
+            SeekFrom::End(x) => self_.index.size.checked_add_signed(x),
+            SeekFrom::Current(x) => self_.cursor.checked_add_signed(x),
+        };
+        self_.cursor = cursor.ok_or(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "seek to a negative position",
+        ))?;
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.cursor))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    use super::*;
+
+    /// compress `content` into seekable format with given frame size
+    fn compress(content: &[u8], frame: usize) -> Vec<u8> {
+        let mut output = Vec::new();
+        let mut table = Vec::new();
+        for chunk in content.chunks(frame) {
+            let compressed = zstd::bulk::compress(chunk, 3).unwrap();
+            table.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            table.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            output.extend_from_slice(&compressed);
+        }
+        let count = (table.len() / 8) as u32;
+        output.extend_from_slice(&SKIPPABLE_MAGIC.to_le_bytes());
+        output.extend_from_slice(&(table.len() as u32 + FOOTER_SIZE as u32).to_le_bytes());
+        output.extend_from_slice(&table);
+        output.extend_from_slice(&count.to_le_bytes());
+        output.push(0);
+        output.extend_from_slice(&SEEKABLE_MAGIC.to_le_bytes());
+        output
+    }
+
+    #[tokio::test]
+    async fn random_read() {
+        let content = include_bytes!("../../../test/nested.tar");
+        let path = std::env::temp_dir().join(format!("mdoj-zstd-{}", std::process::id()));
+        std::fs::File::create(&path)
+            .unwrap()
+            .write_all(&compress(content, 1000))
+            .unwrap();
+
+        let mut file = ZstdFile::new(std::fs::File::open(&path).unwrap())
+            .await
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        for (start, size) in [(0, 10), (995, 10), (2500, 2000), (content.len() - 5, 5)] {
+            file.seek(SeekFrom::Start(start as u64)).await.unwrap();
+            let mut buf = vec![0_u8; size];
+            file.read_exact(&mut buf).await.unwrap();
+            assert_eq!(buf, &content[start..start + size]);
+        }
+    }
+}
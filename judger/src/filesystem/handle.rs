@@ -1,28 +1,91 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
 use super::adapter::Filesystem;
 
 use tokio::io::{AsyncRead, AsyncSeek};
 
-use super::mkdtemp::MkdTemp;
+use super::{bind, mkdtemp::MkdTemp};
+use crate::language::spec::FsBackend;
+
+/// mounted filesystem
+enum Backend {
+    Fuse(fuse3::raw::MountHandle),
+    /// tmpfs mounted on the mountpoint as writable root, with read-only
+    /// bind mounts as `(source, target)`
+    Bind(Arc<[(PathBuf, PathBuf)]>),
+}
 
-pub struct MountHandle(Option<fuse3::raw::MountHandle>, Option<MkdTemp>);
+pub struct MountHandle(Option<Backend>, Option<MkdTemp>);
 
 impl MountHandle {
+    pub(super) fn bind(mountpoint: MkdTemp, binds: Arc<[(PathBuf, PathBuf)]>) -> Self {
+        Self(Some(Backend::Bind(binds)), Some(mountpoint))
+    }
     pub fn get_path(&self) -> &std::path::Path {
         self.1.as_ref().unwrap().get_path()
     }
+    /// backend actually in use, which may differ from spec of plugin
+    pub fn backend(&self) -> FsBackend {
+        match self.0.as_ref().unwrap() {
+            Backend::Fuse(_) => FsBackend::Fuse,
+            Backend::Bind(..) => FsBackend::Bind,
+        }
+    }
+    /// get filesystem to be passed to sandbox
+    pub fn get_view(&self) -> MountView {
+        let binds = match self.0.as_ref().unwrap() {
+            Backend::Fuse(_) => Arc::from([]),
+            Backend::Bind(binds) => binds.clone(),
+        };
+        MountView {
+            path: self.get_path().to_path_buf(),
+            binds,
+        }
+    }
+}
+
+/// filesystem of [`MountHandle`] seen by sandbox
+#[derive(Clone)]
+pub struct MountView {
+    path: PathBuf,
+    binds: Arc<[(PathBuf, PathBuf)]>,
+}
+
+impl crate::sandbox::Filesystem for MountView {
+    fn get_path(&mut self) -> impl AsRef<Path> + Send {
+        self.path.as_path().iter()
+    }
+    fn get_bind_ro(&mut self) -> &[(PathBuf, PathBuf)] {
+        &self.binds
+    }
 }
 
 impl Drop for MountHandle {
     fn drop(&mut self) {
-        let handle = self.0.take().unwrap();
+        let backend = self.0.take().unwrap();
         let mountpoint = self.1.take().unwrap();
         tokio::spawn(async move {
             #[cfg(debug_assertions)]
             {
-                log::warn!("debug mode: wait for 120s before drop mountpoint");
+                tracing::warn!("debug mode: wait for 120s before drop mountpoint");
                 tokio::time::sleep(tokio::time::Duration::from_secs(120)).await;
             }
-            handle.unmount().await.unwrap();
+            match backend {
+                Backend::Fuse(handle) => handle.unmount().await.unwrap(),
+                Backend::Bind(_) => {
+                    // bind mounts live in mount namespace of nsjail, only tmpfs is left
+                    let tmpfs = mountpoint.get_path().to_path_buf();
+                    let result = tokio::task::spawn_blocking(move || bind::umount(&tmpfs))
+                        .await
+                        .unwrap();
+                    if let Err(err) = result {
+                        tracing::warn!("fail to unmount tmpfs: {}", err);
+                    }
+                }
+            }
             drop(mountpoint);
         });
     }
@@ -35,6 +98,6 @@ where
     pub async fn mount(self) -> std::io::Result<MountHandle> {
         let mountpoint = MkdTemp::new();
         let handle = self.raw_mount_with_path(mountpoint.get_path()).await?;
-        Ok(MountHandle(Some(handle), Some(mountpoint)))
+        Ok(MountHandle(Some(Backend::Fuse(handle)), Some(mountpoint)))
     }
 }
@@ -1,6 +1,7 @@
 //! Filesystem module that is mountable(actually mount and
 //! is accessible for user in this operating system)
 mod adapter;
+mod bind;
 mod entry;
 mod handle;
 mod mkdtemp;
@@ -8,4 +9,6 @@ mod resource;
 mod table;
 
 pub use adapter::Template;
-pub use handle::MountHandle;
+pub use bind::BindRoot;
+pub use entry::Rootfs;
+pub use handle::{MountHandle, MountView};
@@ -1,8 +1,9 @@
 use grpc::judger::{
     exec_result as execute_response, ExecResult as ExecuteResponse, JudgeResponse, JudgerCode, Log,
 };
+use tokio::sync::mpsc;
 
-use super::stage::{AssertionMode, StatusCode};
+use super::stage::{AssertionMode, IoFile, StatusCode};
 
 // FIXME: use derive_builder to remove boilerplate code
 
@@ -13,6 +14,7 @@ pub struct JudgeArgs {
     pub(super) output: Vec<Vec<u8>>,
     pub(super) mode: AssertionMode,
     pub(super) source: Vec<u8>,
+    pub(super) io_file: IoFile,
 }
 
 pub struct ExecuteArgs {
@@ -20,12 +22,20 @@ pub struct ExecuteArgs {
     pub(super) cpu: u64,
     pub(super) input: Vec<u8>,
     pub(super) source: Vec<u8>,
+    /// stdin chunks sent after `input`, stdin is closed after `input` if not set
+    pub(super) stdin: Option<mpsc::Receiver<Vec<u8>>>,
 }
 
 pub struct JudgeResult {
     pub status: StatusCode,
     pub time: u64,
     pub memory: u64,
+    /// stderr of failed testcase, empty for accepted one
+    pub stderr: Vec<u8>,
+    /// exit code of failed testcase
+    pub exit_code: Option<i32>,
+    /// terminating signal of failed testcase
+    pub signal: Option<i32>,
 }
 
 impl From<JudgeResult> for JudgeResponse {
@@ -35,6 +45,9 @@ impl From<JudgeResult> for JudgeResponse {
             time: value.time,
             memory: value.memory,
             accuracy: 0, // FIXME: accuracy
+            stderr: (!value.stderr.is_empty()).then_some(value.stderr),
+            exit_code: value.exit_code,
+            signal: value.signal,
         }
     }
 }
@@ -43,35 +56,81 @@ pub struct ExecuteResult {
     pub status: StatusCode,
     pub time: u64,
     pub memory: u64,
-    pub output: Vec<u8>,
+    pub stderr: Vec<u8>,
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
 }
 
-impl From<ExecuteResult> for ExecuteResponse {
+/// name of common signal terminating a program
+fn signal_name(signal: i32) -> Option<&'static str> {
+    Some(match signal {
+        libc::SIGSEGV => "SIGSEGV",
+        libc::SIGFPE => "SIGFPE",
+        libc::SIGABRT => "SIGABRT",
+        libc::SIGBUS => "SIGBUS",
+        libc::SIGILL => "SIGILL",
+        libc::SIGKILL => "SIGKILL",
+        libc::SIGSYS => "SIGSYS",
+        _ => return None,
+    })
+}
+
+/// event of execution, output is sent as it's produced
+pub enum ExecuteEvent {
+    Output(Vec<u8>),
+    Result(ExecuteResult),
+}
+
+impl From<ExecuteResult> for ExecuteEvent {
     fn from(value: ExecuteResult) -> Self {
-        macro_rules! execute_log {
-            ($msg:expr) => {
-                execute_response::Result::Log(Log {
-                    level: 4,
-                    msg: $msg.to_string(),
-                })
-            };
-        }
-        let result = match value.status {
-            StatusCode::Accepted => execute_response::Result::Output(value.output),
-            StatusCode::WrongAnswer => execute_log!("Wrong Answer"),
-            StatusCode::RuntimeError => {
-                execute_log!("Runtime Error, maybe program return non-zero code")
+        ExecuteEvent::Result(value)
+    }
+}
+
+impl From<ExecuteEvent> for ExecuteResponse {
+    fn from(value: ExecuteEvent) -> Self {
+        let value = match value {
+            ExecuteEvent::Output(chunk) => {
+                return ExecuteResponse {
+                    result: Some(execute_response::Result::Output(chunk)),
+                    stderr: None,
+                }
             }
+            ExecuteEvent::Result(x) => x,
+        };
+        let verdict = match value.status {
+            StatusCode::Accepted => "Accepted",
+            StatusCode::WrongAnswer => "Wrong Answer",
+            StatusCode::RuntimeError => "Runtime Error",
             StatusCode::TimeLimitExceeded | StatusCode::RealTimeLimitExceeded => {
-                execute_log!("Time Limit Exceeded")
+                "Time Limit Exceeded"
             }
-            StatusCode::MemoryLimitExceeded => execute_log!("Memory Limit Exceeded"),
-            StatusCode::OutputLimitExceeded => execute_log!("Output Limit Exceeded"),
-            StatusCode::CompileError => execute_log!("Compile Error"),
-            _ => execute_log!("System Error"),
+            StatusCode::MemoryLimitExceeded => "Memory Limit Exceeded",
+            StatusCode::OutputLimitExceeded => "Output Limit Exceeded",
+            StatusCode::CompileError => "Compile Error",
+            _ => "System Error",
+        };
+        let reason = match (value.signal, value.exit_code) {
+            (Some(signal), _) => match signal_name(signal) {
+                Some(name) => format!("({})", name),
+                None => format!("(signal {})", signal),
+            },
+            (None, Some(code)) if code != 0 => format!("(exit code {})", code),
+            _ => String::new(),
+        };
+        let level = match value.status {
+            StatusCode::Accepted => 0,
+            _ => 4,
         };
         ExecuteResponse {
-            result: Some(result),
+            result: Some(execute_response::Result::Log(Log {
+                level,
+                msg: format!(
+                    "{}{}, time: {}ns, memory: {}B",
+                    verdict, reason, value.time, value.memory
+                ),
+            })),
+            stderr: (!value.stderr.is_empty()).then_some(value.stderr),
         }
     }
 }
@@ -83,6 +142,7 @@ pub struct JudgeArgBuilder {
     output: Option<Vec<Vec<u8>>>,
     mode: Option<AssertionMode>,
     source: Option<Vec<u8>>,
+    io_file: IoFile,
 }
 
 impl JudgeArgBuilder {
@@ -94,6 +154,7 @@ impl JudgeArgBuilder {
             output: None,
             mode: None,
             source: None,
+            io_file: IoFile::default(),
         }
     }
     pub fn mem(mut self, mem: u64) -> Self {
@@ -120,6 +181,11 @@ impl JudgeArgBuilder {
         self.source = Some(source);
         self
     }
+    /// read input from and write output to file instead of stdin/stdout
+    pub fn io_file(mut self, input: Option<String>, output: Option<String>) -> Self {
+        self.io_file = IoFile { input, output };
+        self
+    }
     pub fn build(self) -> JudgeArgs {
         JudgeArgs {
             mem: self.mem.expect("mem is not set"),
@@ -128,6 +194,7 @@ impl JudgeArgBuilder {
             output: self.output.expect("output is not set"),
             mode: self.mode.expect("mode is not set"),
             source: self.source.expect("source is not set"),
+            io_file: self.io_file,
         }
     }
 }
@@ -137,6 +204,7 @@ pub struct ExecuteArgBuilder {
     cpu: Option<u64>,
     input: Option<Vec<u8>>,
     source: Option<Vec<u8>>,
+    stdin: Option<mpsc::Receiver<Vec<u8>>>,
 }
 
 impl ExecuteArgBuilder {
@@ -146,6 +214,7 @@ impl ExecuteArgBuilder {
             cpu: None,
             input: None,
             source: None,
+            stdin: None,
         }
     }
     pub fn mem(mut self, mem: u64) -> Self {
@@ -164,12 +233,17 @@ impl ExecuteArgBuilder {
         self.source = Some(source);
         self
     }
+    pub fn stdin(mut self, stdin: mpsc::Receiver<Vec<u8>>) -> Self {
+        self.stdin = Some(stdin);
+        self
+    }
     pub fn build(self) -> ExecuteArgs {
         ExecuteArgs {
             mem: self.mem.expect("mem is not set"),
             cpu: self.cpu.expect("cpu is not set"),
             input: self.input.expect("input is not set"),
             source: self.source.expect("source is not set"),
+            stdin: self.stdin,
         }
     }
 }
@@ -5,19 +5,21 @@ use futures_core::Stream;
 use grpc::judger::LangInfo;
 use rustix::path::Arg;
 use tokio::{
-    fs::{read_dir, File},
+    fs::read_dir,
     io::{AsyncRead, AsyncSeek},
+    sync::mpsc,
 };
+use tracing::{instrument, Instrument, Span};
 use uuid::Uuid;
 
 use crate::filesystem::*;
 
 use super::{
     builder::*,
-    spec::Spec,
+    spec::{FsBackend, Spec},
     stage::{Compiler, StatusCode},
 };
-use crate::Result;
+use crate::{Result, CONFIG};
 
 macro_rules! trys {
     ($ele:expr) => {
@@ -30,10 +32,10 @@ macro_rules! trys {
             }
         }
     };
-    ($ele:expr,$ret:expr) => {
+    ($ele:expr,|$err:ident| $ret:expr) => {
         match $ele {
-            Some(x) => x,
-            None => {
+            Ok(x) => x,
+            Err($err) => {
                 return Box::pin(stream! {yield $ret;});
             }
         }
@@ -42,15 +44,15 @@ macro_rules! trys {
 
 static EXTENSION: &str = "lang";
 
-pub async fn load_plugins(path: impl AsRef<Path>) -> Result<Vec<Plugin<File>>> {
+pub async fn load_plugins(path: impl AsRef<Path>) -> Result<Vec<Plugin<Rootfs>>> {
     let mut plugins = Vec::new();
     let mut dir_list = read_dir(path).await?;
     while let Some(entry) = dir_list.next_entry().await? {
         let path = entry.path();
-        log::trace!("find potential plugin from {}", path.display());
+        tracing::trace!("find potential plugin from {}", path.display());
         let ext = path.extension();
         if path.is_file() && ext.is_some() && ext.unwrap() == EXTENSION {
-            log::info!("load plugin from {}", path.display());
+            tracing::info!("load plugin from {}", path.display());
             let plugin = Plugin::new(path).await?;
             plugins.push(plugin);
         }
@@ -62,7 +64,7 @@ pub struct PluginMap<F>(BTreeMap<Uuid, Plugin<F>>)
 where
     F: AsyncRead + AsyncSeek + Unpin + Send + 'static;
 
-impl PluginMap<File> {
+impl PluginMap<Rootfs> {
     pub async fn new(path: impl AsRef<Path>) -> Result<Self> {
         let plugins = load_plugins(path).await?;
         let mut map = BTreeMap::new();
@@ -72,20 +74,23 @@ impl PluginMap<File> {
         }
         Ok(Self(map))
     }
-    pub fn get(&self, id: &Uuid) -> Option<Plugin<File>> {
+    pub fn get(&self, id: &Uuid) -> Option<Plugin<Rootfs>> {
         self.0.get(id).cloned()
     }
-    pub fn iter(&self) -> impl Iterator<Item = &Plugin<File>> {
+    pub fn iter(&self) -> impl Iterator<Item = &Plugin<Rootfs>> {
         self.0.values()
     }
 }
 
 impl JudgeResult {
-    fn compile_error() -> Self {
+    fn compile_error(stderr: Vec<u8>) -> Self {
         Self {
             status: StatusCode::CompileError,
             time: 0,
             memory: 0,
+            stderr,
+            exit_code: None,
+            signal: None,
         }
     }
 }
@@ -96,6 +101,8 @@ where
 {
     pub(super) spec: Arc<Spec>,
     pub(super) template: Arc<Template<F>>,
+    /// extracted rootfs, use FUSE filesystem if not set
+    pub(super) bind_root: Option<Arc<BindRoot>>,
 }
 
 impl<F> Clone for Plugin<F>
@@ -106,11 +113,12 @@ where
         Self {
             spec: self.spec.clone(),
             template: self.template.clone(),
+            bind_root: self.bind_root.clone(),
         }
     }
 }
 
-impl Plugin<File> {
+impl Plugin<Rootfs> {
     pub async fn new(path: impl AsRef<Path> + Clone) -> Result<Self> {
         let template = Arc::new(Template::new(path.clone()).await?);
         let spec_source = template
@@ -119,7 +127,20 @@ impl Plugin<File> {
             .unwrap_or_else(|| panic!("spec.toml not found in plugin {}", path.as_ref().display()));
         let spec = Arc::new(Spec::from_str(&spec_source.to_string_lossy()));
 
-        Ok(Self { spec, template })
+        let bind_root = match spec.filesystem {
+            FsBackend::Bind if CONFIG.get().rootless => {
+                tracing::warn!("bind filesystem is unavailable in rootless mode, fallback to fuse");
+                None
+            }
+            FsBackend::Bind => Some(Arc::new(BindRoot::new(path).await?)),
+            FsBackend::Fuse => None,
+        };
+
+        Ok(Self {
+            spec,
+            template,
+            bind_root,
+        })
     }
 }
 
@@ -131,15 +152,30 @@ where
     pub fn get_info(&self) -> &LangInfo {
         &self.spec.info
     }
+    /// mount rootfs of plugin, with `source` written to source file
+    pub async fn mount(&self, source: Vec<u8>) -> Result<MountHandle> {
+        Ok(match &self.bind_root {
+            Some(bind_root) => {
+                bind_root
+                    .mount(self.spec.fs_limit, &self.spec.file, source)
+                    .await?
+            }
+            None => {
+                let filesystem = self.template.as_filesystem(self.spec.fs_limit);
+                filesystem.insert_by_path(self.spec.file.as_os_str(), source);
+                filesystem.mount().await?
+            }
+        })
+    }
     /// get compiler from plugin
+    #[instrument(skip_all, level = "debug", fields(lang = self.spec.info.lang_name.as_str()))]
     pub async fn as_compiler(&self, source: Vec<u8>) -> Result<Compiler> {
-        log::trace!(
+        tracing::trace!(
             "create compiler from plugin {}",
             self.spec.info.lang_name.as_str()
         );
-        let filesystem = self.template.as_filesystem(self.spec.fs_limit);
-        filesystem.insert_by_path(self.spec.file.as_os_str(), source);
-        Ok(Compiler::new(self.spec.clone(), filesystem.mount().await?))
+        let handle = self.mount(source).await?;
+        Ok(Compiler::new(self.spec.clone(), handle))
     }
     /// judge
     ///
@@ -153,17 +189,34 @@ where
     ) -> Pin<Box<dyn Stream<Item = Result<JudgeResult>> + Send>> {
         let compiler = trys!(self.as_compiler(args.source).await);
         let maybe_runner = trys!(compiler.compile().await);
-        let mut runner = trys!(maybe_runner, Ok(JudgeResult::compile_error()));
+        let mut runner = trys!(maybe_runner, |stderr| Ok(JudgeResult::compile_error(
+            stderr
+        )));
 
         let mem_cpu = (args.mem, args.cpu);
         let mode = args.mode;
+        let io_file = args.io_file;
         let testcases = args.input.into_iter().zip(args.output.into_iter());
+        let parent = Span::current();
         Box::pin(try_stream! {
-            for (input,output) in testcases{
-                let judger = runner.judge(mem_cpu, input).await?;
+            for (index, (input,output)) in testcases.enumerate(){
+                let span = tracing::info_span!(
+                    parent: &parent,
+                    "run",
+                    testcase = index,
+                    verdict = tracing::field::Empty
+                );
+                let judger = runner
+                    .judge(mem_cpu, input, &io_file)
+                    .instrument(span.clone())
+                    .await?;
+
+                let code = judger.get_code(&output, mode);
+                span.record("verdict", tracing::field::debug(code));
+                tracing::info!(parent: &span, monotonic_counter.verdict = 1, verdict = ?code);
 
                 yield judger.get_result(&output, mode);
-                if judger.get_code(&output, mode)!=StatusCode::Accepted{
+                if code!=StatusCode::Accepted{
                     break;
                 }
             }
@@ -173,23 +226,46 @@ where
     ///
     /// The process can be described as:
     /// 1. compile the source code
-    /// 2. run the compiled code
-    /// 3. stream the output to client
-    pub async fn execute(&self, args: ExecuteArgs) -> Result<ExecuteResult> {
-        let compiler = self.as_compiler(args.source).await?;
-        let maybe_runner = compiler.compile().await?;
-        match maybe_runner {
-            Some(mut runner) => {
-                let executor = runner.stream((args.mem, args.cpu), args.input).await?;
-                Ok(executor.get_result())
-            }
-            None => Ok(ExecuteResult {
-                status: StatusCode::CompileError,
-                time: 0,
-                memory: 0,
-                output: Vec::new(),
-            }),
+    /// 2. run the compiled code, stream the output to client as it's produced
+    /// 3. send the verdict and stats
+    pub async fn execute(
+        &self,
+        args: ExecuteArgs,
+    ) -> Pin<Box<dyn Stream<Item = Result<ExecuteEvent>> + Send>> {
+        let compiler = trys!(self.as_compiler(args.source).await);
+        let maybe_runner = trys!(compiler.compile().await);
+        let mut runner = trys!(maybe_runner, |stderr| Ok(ExecuteResult {
+            status: StatusCode::CompileError,
+            time: 0,
+            memory: 0,
+            stderr,
+            exit_code: None,
+            signal: None,
         }
+        .into()));
+
+        let mem_cpu = (args.mem, args.cpu);
+        let (input, stdin) = (args.input, args.stdin);
+        Box::pin(try_stream! {
+            let (tx, mut rx) = mpsc::channel(16);
+            let stream = runner.stream(mem_cpu, input, stdin, tx);
+            tokio::pin!(stream);
+            // yield can't be used inside select!, so the event is taken out first
+            let streamer = loop {
+                let event = tokio::select! {
+                    Some(chunk) = rx.recv() => Ok(chunk),
+                    x = &mut stream => Err(x),
+                };
+                match event {
+                    Ok(chunk) => yield ExecuteEvent::Output(chunk),
+                    Err(x) => break x?,
+                }
+            };
+            while let Ok(chunk) = rx.try_recv() {
+                yield ExecuteEvent::Output(chunk);
+            }
+            yield streamer.get_result().into();
+        })
     }
     /// get size of memory that should be reserved for the sandbox to prevent OOM
     pub fn get_memory_reserved(&self, mem: u64) -> u64 {
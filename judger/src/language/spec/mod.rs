@@ -1,6 +1,7 @@
 use std::{ffi::OsString, time::Duration};
 
 use grpc::judger::LangInfo;
+use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::sandbox::{Cpu, Memory, Stat};
@@ -67,9 +68,31 @@ impl MemFactor {
     }
 }
 
+/// filesystem backend of sandbox
+#[derive(Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum FsBackend {
+    /// userspace filesystem serving rootfs from tar file
+    #[default]
+    Fuse,
+    /// extracted rootfs bind-mounted read-only on tmpfs by nsjail, faster for IO-heavy workload
+    Bind,
+}
+
+impl FsBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FsBackend::Fuse => "fuse",
+            FsBackend::Bind => "bind",
+        }
+    }
+}
+
 pub struct Spec {
     pub id: Uuid,
+    pub filesystem: FsBackend,
     pub fs_limit: u64,
+    pub stderr_limit: u64,
     pub compile_limit: Stat,
     judge_cpu_factor: CpuFactor,
     judge_mem_factor: MemFactor,
@@ -109,7 +132,9 @@ impl Spec {
         Self {
             info: LangInfo::from(&raw),
             id: raw.id,
+            filesystem: raw.filesystem,
             fs_limit: raw.fs_limit.unwrap(),
+            stderr_limit: raw.stderr_limit.unwrap(),
             compile_limit: Stat {
                 cpu: Cpu {
                     kernel: raw.compile.rt_time.unwrap(),
@@ -2,14 +2,19 @@ use grpc::judger::LangInfo;
 use serde::Deserialize;
 use uuid::Uuid;
 
+use super::FsBackend;
+
 #[derive(Deserialize)]
 pub struct Raw {
     pub fs_limit: Option<u64>,
+    pub stderr_limit: Option<u64>,
     pub file: String,
     pub info: String,
     pub extension: String,
     pub name: String,
     pub id: Uuid,
+    #[serde(default)]
+    pub filesystem: FsBackend,
     pub compile: RawCompile,
     pub judge: RawJudge,
 }
@@ -30,6 +35,9 @@ impl Raw {
         if self.fs_limit.is_none() {
             self.fs_limit = Some(67108864);
         }
+        if self.stderr_limit.is_none() {
+            self.stderr_limit = Some(65536);
+        }
         self.compile.fill();
         self.judge.fill();
     }
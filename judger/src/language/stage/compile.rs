@@ -1,12 +1,17 @@
-use std::{path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    filesystem::MountHandle,
+    filesystem::{MountHandle, MountView},
     language::spec::Spec,
     sandbox::{Context, Limit, Process},
     Result,
 };
 
+use tracing::instrument;
+
 use super::Runner;
 
 /// First stage, compile the source code
@@ -23,21 +28,29 @@ impl Compiler {
     pub fn new(spec: Arc<Spec>, handle: MountHandle) -> Self {
         Self { spec, handle }
     }
-    pub async fn compile(self) -> Result<Option<Runner>> {
+    /// return stderr of the compiler(truncated at `stderr_limit`) if it fails
+    #[instrument(skip_all, level = "info", name = "compile")]
+    pub async fn compile(self) -> Result<std::result::Result<Runner, Vec<u8>>> {
         let ctx = CompileCtx {
             spec: self.spec.clone(),
-            path: self.handle.get_path().to_path_buf(),
+            fs: self.handle.get_view(),
         };
+        let start = Instant::now();
         let process = Process::new(ctx)?;
         let corpse = process.wait(Vec::new()).await?;
+        tracing::info!(
+            histogram.compile_time = start.elapsed().as_secs_f64() * 1000.0,
+            success = corpse.success(),
+            filesystem = self.handle.backend().as_str()
+        );
         if !corpse.success() {
-            log::trace!("compile failed, corpse: {:?}", corpse);
+            tracing::trace!("compile failed, corpse: {:?}", corpse);
             // tokio::time::sleep(Duration::from_secs(600)).await;
-            return Ok(None);
+            return Ok(Err(corpse.stderr().to_vec()));
         }
 
         let runner = Runner::new(self.handle, self.spec);
-        Ok(Some(runner))
+        Ok(Ok(runner))
     }
 }
 
@@ -46,7 +59,7 @@ impl Compiler {
 /// See [`Context`] for more information
 struct CompileCtx {
     spec: Arc<Spec>,
-    path: PathBuf,
+    fs: MountView,
 }
 
 impl Limit for CompileCtx {
@@ -59,15 +72,18 @@ impl Limit for CompileCtx {
     fn get_output(&mut self) -> u64 {
         self.spec.compile_limit.output
     }
+    fn get_stderr(&mut self) -> u64 {
+        self.spec.stderr_limit
+    }
     fn get_walltime(&mut self) -> Duration {
         self.spec.compile_limit.walltime
     }
 }
 
 impl Context for CompileCtx {
-    type FS = PathBuf;
+    type FS = MountView;
     fn get_fs(&mut self) -> Self::FS {
-        self.path.clone()
+        self.fs.clone()
     }
     fn get_args(&mut self) -> impl Iterator<Item = &std::ffi::OsStr> {
         self.spec.compile_command.iter().map(|arg| arg.as_os_str())
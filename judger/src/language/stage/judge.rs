@@ -11,11 +11,17 @@ use super::{AssertionMode, StatusCode};
 pub struct Judger {
     spec: Arc<Spec>,
     corpse: Corpse,
+    /// content of output file, compare it instead of stdout if set
+    file_output: Option<Vec<u8>>,
 }
 
 impl Judger {
-    pub fn new(spec: Arc<Spec>, corpse: Corpse) -> Self {
-        Self { spec, corpse }
+    pub fn new(spec: Arc<Spec>, corpse: Corpse, file_output: Option<Vec<u8>>) -> Self {
+        Self {
+            spec,
+            corpse,
+            file_output,
+        }
     }
     pub fn stat(&self) -> Stat {
         let stat = self.corpse.stat();
@@ -25,7 +31,10 @@ impl Judger {
     //     self.corpse.stream_stdout()
     // }
     fn assert_output(&self, output: &[u8], mode: AssertionMode) -> StatusCode {
-        let input = self.corpse.stdout();
+        let input = match &self.file_output {
+            Some(x) => x.as_slice(),
+            None => self.corpse.stdout(),
+        };
         match mode {
             AssertionMode::SkipSpace => {
                 // skip space and newline, continuous space and single space is consider different
@@ -99,7 +108,7 @@ impl Judger {
             },
             Err(reason) => match reason {
                 MonitorKind::Cpu => StatusCode::TimeLimitExceeded,
-                MonitorKind::Memory => StatusCode::MemoryLimitExceeded,
+                MonitorKind::Memory | MonitorKind::OomKill => StatusCode::MemoryLimitExceeded,
                 MonitorKind::Output => StatusCode::OutputLimitExceeded,
                 MonitorKind::Walltime => StatusCode::RealTimeLimitExceeded,
             },
@@ -108,10 +117,21 @@ impl Judger {
     pub fn get_result(&self, output: &[u8], mode: AssertionMode) -> JudgeResult {
         let status = self.get_code(output, mode);
         let stat = self.stat();
+        let (stderr, exit_code, signal) = match status {
+            StatusCode::Accepted => (Vec::new(), None, None),
+            _ => (
+                self.corpse.stderr().to_vec(),
+                self.corpse.exit_code(),
+                self.corpse.signal(),
+            ),
+        };
         JudgeResult {
             status,
             time: stat.cpu.total,
             memory: stat.memory.total,
+            stderr,
+            exit_code,
+            signal,
         }
     }
 }
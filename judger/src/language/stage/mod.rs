@@ -9,7 +9,7 @@ mod stream;
 
 pub use compile::Compiler;
 use grpc::{judger::JudgeMatchRule, judger::JudgerCode};
-pub use run::Runner;
+pub use run::{IoFile, Runner};
 
 /// internal status code, use to decouple the grpc status code
 ///
@@ -72,9 +72,9 @@ impl From<StatusCode> for JudgerCode {
             StatusCode::TimeLimitExceeded => Self::Tle,
             StatusCode::MemoryLimitExceeded => Self::Mle,
             StatusCode::OutputLimitExceeded => Self::Ole,
-            StatusCode::RealTimeLimitExceeded => Self::Na,
+            StatusCode::RealTimeLimitExceeded => Self::Rtle,
             StatusCode::CompileError => Self::Ce,
-            StatusCode::SystemError => Self::Na,
+            StatusCode::SystemError => Self::Se,
         }
     }
 }
@@ -1,7 +1,17 @@
-use std::{path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    os::fd::OwnedFd,
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+
+use rustix::fs::{self, AtFlags, FileType, Mode, OFlags, ResolveFlags};
+use tokio::{sync::mpsc, task::spawn_blocking};
 
 use crate::{
-    filesystem::MountHandle,
+    filesystem::{MountHandle, MountView},
     language::spec::Spec,
     sandbox::{Context, Cpu, Limit, Memory, Process, Stat},
     Result,
@@ -9,6 +19,15 @@ use crate::{
 
 use super::{judge::Judger, stream::Streamer};
 
+/// redirect input/output of testcase to file in sandbox
+///
+/// file names are relative to root of sandbox
+#[derive(Clone, Default)]
+pub struct IoFile {
+    pub input: Option<String>,
+    pub output: Option<String>,
+}
+
 /// Second stage, run the compiled code
 pub struct Runner {
     filesystem: MountHandle,
@@ -19,34 +38,119 @@ impl Runner {
     pub fn new(filesystem: MountHandle, spec: Arc<Spec>) -> Self {
         Self { filesystem, spec }
     }
-    pub async fn judge(&mut self, (mem, cpu): (u64, u64), input: Vec<u8>) -> Result<Judger> {
+    pub async fn judge(
+        &mut self,
+        (mem, cpu): (u64, u64),
+        input: Vec<u8>,
+        io_file: &IoFile,
+    ) -> Result<Judger> {
+        let root = self.filesystem.get_path().to_path_buf();
+        if let Some(output) = io_file.output.clone() {
+            // remove output of previous testcase
+            let root = root.clone();
+            spawn_blocking(move || -> io::Result<()> {
+                Ok(fs::unlinkat(open_root(&root)?, output, AtFlags::empty())?)
+            })
+            .await
+            .unwrap()
+            .ok();
+        }
+        let stdin = match io_file.input.clone() {
+            Some(file) => {
+                let root = root.clone();
+                spawn_blocking(move || {
+                    let flags = OFlags::WRONLY | OFlags::CREATE | OFlags::TRUNC;
+                    open_beneath(&root, &file, flags)?.write_all(&input)
+                })
+                .await
+                .unwrap()?;
+                Vec::new()
+            }
+            None => input,
+        };
+
         let ctx = RunCtx {
             spec: self.spec.clone(),
-            path: self.filesystem.get_path().to_path_buf(),
+            fs: self.filesystem.get_view(),
             limit: self.spec.get_judge_limit(cpu, mem),
         };
         let process = Process::new(ctx)?;
-        let corpse = process.wait(input).await?;
-        Ok(Judger::new(self.spec.clone(), corpse))
+        let corpse = process.wait(stdin).await?;
+
+        let file_output = match io_file.output.clone() {
+            // missing output file is treated as empty output
+            Some(file) => Some(
+                spawn_blocking(move || {
+                    let mut buf = Vec::new();
+                    open_beneath(&root, &file, OFlags::RDONLY)?.read_to_end(&mut buf)?;
+                    Ok::<_, io::Error>(buf)
+                })
+                .await
+                .unwrap()
+                .unwrap_or_default(),
+            ),
+            None => None,
+        };
+        Ok(Judger::new(self.spec.clone(), corpse, file_output))
     }
-    pub async fn stream(&mut self, (mem, cpu): (u64, u64), input: Vec<u8>) -> Result<Streamer> {
+    /// run the code, and send stdout to `tx` as it's produced
+    ///
+    /// if `stdin` is set, its chunks are written to stdin of the process after `input`
+    pub async fn stream(
+        &mut self,
+        (mem, cpu): (u64, u64),
+        input: Vec<u8>,
+        stdin: Option<mpsc::Receiver<Vec<u8>>>,
+        tx: mpsc::Sender<Vec<u8>>,
+    ) -> Result<Streamer> {
         let ctx = RunCtx {
             spec: self.spec.clone(),
-            path: self.filesystem.get_path().to_path_buf(),
+            fs: self.filesystem.get_view(),
             limit: self.spec.get_judge_limit(cpu, mem),
         };
         let process = Process::new(ctx)?;
-        let corpse = process.wait(input).await?;
+        let corpse = match stdin {
+            Some(stdin) => process.wait_interactive(input, stdin, tx).await?,
+            None => process.wait_with_stream(input, tx).await?,
+        };
         Ok(Streamer::new(corpse))
     }
 }
 
+fn open_root(root: &Path) -> io::Result<OwnedFd> {
+    let flags = OFlags::PATH | OFlags::DIRECTORY | OFlags::CLOEXEC;
+    Ok(fs::open(root, flags, Mode::empty())?)
+}
+
+/// open `file` in `root` as judger, which must be a regular file
+///
+/// User code could replace it with symlink or FIFO, so symlinks are never
+/// followed, and path must be resolved beneath `root`.
+fn open_beneath(root: &Path, file: &str, flags: OFlags) -> io::Result<File> {
+    let flags = flags | OFlags::NOFOLLOW | OFlags::NONBLOCK | OFlags::NOCTTY | OFlags::CLOEXEC;
+    let resolve = ResolveFlags::BENEATH | ResolveFlags::NO_SYMLINKS | ResolveFlags::NO_MAGICLINKS;
+    let fd = fs::openat2(
+        open_root(root)?,
+        file,
+        flags,
+        Mode::from_bits_truncate(0o644),
+        resolve,
+    )?;
+    if FileType::from_raw_mode(fs::fstat(&fd)?.st_mode) != FileType::RegularFile {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "not a regular file",
+        ));
+    }
+    Ok(File::from(fd))
+}
+
 /// Process context for run stage
 ///
 /// See [`Context`] for more information
 struct RunCtx {
     spec: Arc<Spec>,
-    path: PathBuf,
+    fs: MountView,
     limit: Stat,
 }
 
@@ -60,15 +164,18 @@ impl Limit for RunCtx {
     fn get_output(&mut self) -> u64 {
         self.limit.output
     }
+    fn get_stderr(&mut self) -> u64 {
+        self.spec.stderr_limit
+    }
     fn get_walltime(&mut self) -> Duration {
         self.limit.walltime
     }
 }
 
 impl Context for RunCtx {
-    type FS = PathBuf;
+    type FS = MountView;
     fn get_fs(&mut self) -> Self::FS {
-        self.path.clone()
+        self.fs.clone()
     }
     fn get_args(&mut self) -> impl Iterator<Item = &std::ffi::OsStr> {
         self.spec.judge_command.iter().map(|s| s.as_ref())
@@ -5,7 +5,9 @@ use crate::{
 
 use super::StatusCode;
 
-/// Third stage of exec, stream execution result to client
+/// Third stage of exec, report execution result to client
+///
+/// Output is streamed while running, see [`super::Runner::stream`]
 pub struct Streamer {
     corpse: Corpse,
 }
@@ -22,7 +24,7 @@ impl Streamer {
             },
             Err(reason) => match reason {
                 MonitorKind::Cpu => StatusCode::TimeLimitExceeded,
-                MonitorKind::Memory => StatusCode::MemoryLimitExceeded,
+                MonitorKind::Memory | MonitorKind::OomKill => StatusCode::MemoryLimitExceeded,
                 MonitorKind::Output => StatusCode::OutputLimitExceeded,
                 MonitorKind::Walltime => StatusCode::RealTimeLimitExceeded,
             },
@@ -34,7 +36,9 @@ impl Streamer {
             status: self.get_code(),
             time: stat.cpu.total,
             memory: stat.memory.total,
-            output: self.corpse.stdout().to_vec(),
+            stderr: self.corpse.stderr().to_vec(),
+            exit_code: self.corpse.exit_code(),
+            signal: self.corpse.signal(),
         }
     }
 }
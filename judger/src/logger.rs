@@ -0,0 +1,171 @@
+use opentelemetry::{global, trace::TraceError, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{
+    metrics::SdkMeterProvider,
+    propagation::TraceContextPropagator,
+    runtime,
+    trace::{BatchConfig, RandomIdGenerator, Sampler, Tracer},
+    Resource,
+};
+use opentelemetry_semantic_conventions::{
+    resource::{DEPLOYMENT_ENVIRONMENT, SERVICE_NAME, SERVICE_VERSION},
+    SCHEMA_URL,
+};
+use tonic::metadata::{KeyRef, MetadataMap};
+use tracing::{Level, Span};
+use tracing_opentelemetry::{MetricsLayer, OpenTelemetryLayer, OpenTelemetrySpanExt};
+use tracing_subscriber::{
+    filter::LevelFilter, layer::SubscriberExt, reload, util::SubscriberInitExt, Layer, Registry,
+};
+
+use crate::{
+    metrics::{PACKAGE_NAME, REGISTRY},
+    CONFIG,
+};
+
+fn resource() -> Resource {
+    Resource::from_schema_url(
+        [
+            KeyValue::new(SERVICE_NAME, PACKAGE_NAME),
+            KeyValue::new(SERVICE_VERSION, env!("CARGO_PKG_VERSION")),
+            #[cfg(debug_assertions)]
+            KeyValue::new(DEPLOYMENT_ENVIRONMENT, "development"),
+            #[cfg(not(debug_assertions))]
+            KeyValue::new(DEPLOYMENT_ENVIRONMENT, "production"),
+        ],
+        SCHEMA_URL,
+    )
+}
+
+// Construct MeterProvider for MetricsLayer, which is exported by prometheus registry
+fn init_meter_provider() -> Result<SdkMeterProvider, Box<dyn std::error::Error>> {
+    let exporter = opentelemetry_prometheus::exporter()
+        .with_registry(REGISTRY.clone())
+        .build()?;
+    let meter_provider = SdkMeterProvider::builder()
+        .with_resource(resource())
+        .with_reader(exporter)
+        .build();
+
+    global::set_meter_provider(meter_provider.clone());
+
+    Ok(meter_provider)
+}
+
+// Construct Tracer for OpenTelemetryLayer
+fn init_tracer(endpoint: &str) -> Result<Tracer, TraceError> {
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_trace_config(
+            opentelemetry_sdk::trace::Config::default()
+                // respect sampling decision made by backend
+                .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+                    1.0,
+                ))))
+                .with_id_generator(RandomIdGenerator::default())
+                .with_resource(resource()),
+        )
+        .with_batch_config(BatchConfig::default())
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .install_batch(runtime::Tokio)
+}
+
+fn level() -> Level {
+    match CONFIG.get().log {
+        0 => Level::TRACE,
+        1 => Level::DEBUG,
+        2 => Level::INFO,
+        3 => Level::WARN,
+        4 => Level::ERROR,
+        _ => Level::INFO,
+    }
+}
+
+/// handle to change log level at runtime
+pub type LevelHandle = reload::Handle<LevelFilter, Registry>;
+
+/// apply log level from current config
+pub fn reload_level(handle: &LevelHandle) {
+    if let Err(err) = handle.reload(LevelFilter::from_level(level())) {
+        tracing::warn!("fail to reload log level: {}", err);
+    }
+}
+
+/// Guard for opentelemetry-related termination processing
+///
+/// Drop it to flush pending spans
+pub struct OtelGuard {
+    meter_provider: SdkMeterProvider,
+    level: LevelHandle,
+}
+
+impl OtelGuard {
+    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+
+        let meter_provider = init_meter_provider()?;
+
+        let (level, level_handle) = reload::Layer::new(LevelFilter::from_level(level()));
+        let tracer = match CONFIG.get().opentelemetry.as_deref() {
+            Some(endpoint) => Some(OpenTelemetryLayer::new(init_tracer(endpoint)?)),
+            None => None,
+        };
+        // log level only applies to log and trace, metrics are always recorded
+        tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .and_then(tracer)
+                    .with_filter(level),
+            )
+            .with(MetricsLayer::new(meter_provider.clone()).with_filter(LevelFilter::DEBUG))
+            .init();
+
+        Ok(OtelGuard {
+            meter_provider,
+            level: level_handle,
+        })
+    }
+    pub fn level_handle(&self) -> LevelHandle {
+        self.level.clone()
+    }
+}
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        if let Err(err) = self.meter_provider.shutdown() {
+            eprintln!("{err:?}");
+        }
+        global::shutdown_tracer_provider();
+    }
+}
+
+/// Extract trace context from metadata of grpc request
+struct MetadataExtractor<'a>(&'a MetadataMap);
+
+impl<'a> opentelemetry::propagation::Extractor for MetadataExtractor<'a> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|x| x.to_str().ok())
+    }
+    fn keys(&self) -> Vec<&str> {
+        self.0
+            .keys()
+            .map(|key| match key {
+                KeyRef::Ascii(x) => x.as_str(),
+                KeyRef::Binary(x) => x.as_str(),
+            })
+            .collect()
+    }
+}
+
+/// set parent of `span` to the trace context propagated by caller(backend)
+///
+/// It's a no-op if caller doesn't propagate trace context
+pub fn set_remote_parent(span: &Span, meta: &MetadataMap) {
+    let cx =
+        global::get_text_map_propagator(|propagator| propagator.extract(&MetadataExtractor(meta)));
+    span.set_parent(cx);
+}
@@ -2,13 +2,17 @@ mod config;
 mod error;
 mod filesystem;
 mod language;
+mod logger;
+mod metrics;
 mod sandbox;
+mod selftest;
 mod server;
 
 pub use config::CONFIG;
 
 use grpc::judger::judger_server::JudgerServer;
-use server::Server;
+use server::{MemoryPool, Server};
+use tokio::signal::unix::{signal, SignalKind};
 
 #[cfg(not(debug_assertions))]
 #[global_allocator]
@@ -16,30 +20,68 @@ static GLOBAL: tikv_jemallocator::Jemalloc = tikv_jemallocator::Jemalloc;
 
 type Result<T> = std::result::Result<T, error::Error>;
 
+/// reload config on SIGHUP
+async fn reload_on_hangup(level: logger::LevelHandle, pool: MemoryPool) {
+    let mut hangup = signal(SignalKind::hangup()).unwrap();
+    while hangup.recv().await.is_some() {
+        match CONFIG.reload() {
+            Ok(old) => {
+                let new = CONFIG.get();
+                logger::reload_level(&level);
+                pool.resize(old.memory, new.memory);
+                tracing::info!("config reloaded");
+            }
+            Err(err) => tracing::warn!("fail to reload config, keep using current one: {}", err),
+        }
+    }
+}
+
 #[tokio::main]
 async fn main() {
-    // FIXME: use CONFIG for logging
-    env_logger::Builder::from_default_env()
-        .filter_level(log::LevelFilter::Debug)
-        .try_init()
-        .ok();
+    if std::env::args().any(|arg| arg == "--check-config") {
+        match config::load_config() {
+            Ok(config) => print!("{}", config.to_redacted_string()),
+            Err(err) => {
+                eprintln!("invalid config: {}", err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if std::env::args().nth(1).as_deref() == Some("selftest") {
+        let success = selftest::run(std::env::args().nth(2)).await;
+        std::process::exit(if success { 0 } else { 1 });
+    }
+
+    let guard = logger::OtelGuard::new().expect("failed to initialize tracing");
 
     // FIXME: print traceback on every error
     let default_panic = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |info| {
-        log::error!("something panic, exiting...");
+        tracing::error!("something panic, exiting...");
         default_panic(info);
         std::process::exit(1);
     }));
 
     #[cfg(debug_assertions)]
-    log::warn!("running debug build");
+    tracing::warn!("running debug build");
+
+    if let Some(addr) = CONFIG.get().metrics {
+        tokio::spawn(metrics::serve(addr));
+    }
 
     let server = Server::new().await.unwrap();
+    tokio::spawn(reload_on_hangup(guard.level_handle(), server.memory_pool()));
+
+    let mut builder = tonic::transport::Server::builder();
+    if let Some(tls) = &CONFIG.get().tls {
+        let tls = tls.load().expect("fail to load tls certificate");
+        builder = builder.tls_config(tls).unwrap();
+    }
 
-    tonic::transport::Server::builder()
+    builder
         .add_service(JudgerServer::new(server))
-        .serve(CONFIG.address)
+        .serve(CONFIG.get().address)
         .await
         .unwrap();
 }
@@ -4,7 +4,7 @@ macro_rules! async_loop {
         async move {
             loop {
                 $e
-                tokio::time::sleep($crate::sandbox::monitor::mem_cpu::MONITOR_ACCURACY).await;
+                tokio::time::sleep($crate::sandbox::monitor::mem_cpu::POLL_INTERVAL).await;
             }
         }
     };
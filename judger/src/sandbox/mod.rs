@@ -27,6 +27,10 @@ pub trait Limit {
     fn get_cpu(&mut self) -> Cpu;
     fn get_memory(&mut self) -> Memory;
     fn get_output(&mut self) -> u64;
+    /// size limit of captured stderr, the rest is discarded
+    fn get_stderr(&mut self) -> u64 {
+        64 * 1024
+    }
     fn get_walltime(&mut self) -> Duration {
         Duration::from_secs(60 * 30)
     }
@@ -34,6 +38,10 @@ pub trait Limit {
 
 pub trait Filesystem {
     fn get_path(&mut self) -> impl AsRef<Path> + Send;
+    /// host paths bind-mounted read-only into sandbox, as `(source, target)`
+    fn get_bind_ro(&mut self) -> &[(PathBuf, PathBuf)] {
+        &[]
+    }
 }
 
 impl Filesystem for PathBuf {
@@ -12,7 +12,7 @@ pub enum MonitorKind {
 lazy_static::lazy_static! {
     /// type of monitor for cpu
     pub static ref MONITER_KIND: MonitorKind = {
-        let kind=match crate::CONFIG.accounting {
+        let kind=match crate::CONFIG.get().accounting {
             Accounting::Auto =>match hierarchies::auto().v2(){
                 true=>MonitorKind::Cpu,
                 false=>MonitorKind::CpuAcct
@@ -21,8 +21,8 @@ lazy_static::lazy_static! {
             Accounting::Cpu => MonitorKind::Cpu,
         };
         match kind.heir().v2(){
-            true=>log::info!("using cgroup v2"),
-            false=>log::info!("using cgroup v1")
+            true=>tracing::info!("using cgroup v2"),
+            false=>tracing::info!("using cgroup v1")
         }
         kind
     };
@@ -1,6 +1,4 @@
-use crate::async_loop;
-
-use self::wrapper::CgroupWrapperOwned;
+use self::wrapper::{CgroupStat, CgroupWrapperOwned};
 
 use super::{stat::*, *};
 use cgroups_rs::{cgroup_builder::CgroupBuilder, Cgroup};
@@ -8,7 +6,13 @@ use std::sync::{atomic::Ordering, Arc};
 use tokio::{select, time::*};
 
 /// maximum allow time deviation for cpu monitor
-pub const MONITOR_ACCURACY: Duration = Duration::from_millis(80);
+pub const MONITOR_ACCURACY: Duration = Duration::from_millis(10);
+/// interval of polling statistics that has no event to wait for
+pub const POLL_INTERVAL: Duration = Duration::from_millis(80);
+/// period of cpu bandwidth control(`cpu.max`) in microsecond
+///
+/// quota is set to the same value, so the cgroup use at most one cpu
+const CPU_PERIOD: u64 = 100_000;
 
 lazy_static::lazy_static! {
     pub static ref CG_PATH_COUNTER: AtomicUsize=AtomicUsize::new(0);
@@ -19,18 +23,64 @@ async fn monitor(cgroup: Arc<Cgroup>, cpu: Cpu) -> MonitorKind {
 
     let oom_signal = wrapper.oom_signal();
 
-    let cpu_future = async_loop!({
-        if Cpu::out_of_resources(&cpu, wrapper.cpu()) {
-            break;
+    // cpu time cannot grow faster than walltime because of quota,
+    // so sleep until remaining budget could be used up
+    let cpu_future = async {
+        loop {
+            let usage = wrapper.cpu();
+            if Cpu::out_of_resources(&cpu, usage.clone()) {
+                let overshoot = overshoot(&cpu, &usage);
+                tracing::trace!("cpu limit overshoot by {:?}", overshoot);
+                tracing::info!(histogram.cpu_overshoot = overshoot.as_secs_f64() * 1000.0);
+                break;
+            }
+            sleep(remain(&cpu, &usage).max(MONITOR_ACCURACY)).await;
         }
-    });
+    };
 
     select! {
         _ = cpu_future=> MonitorKind::Cpu,
-        _ = oom_signal.wait()=> MonitorKind::Memory
+        _ = oom_signal.wait()=> MonitorKind::OomKill
     }
 }
 
+/// cpu time left before any of the limit is reached
+fn remain(limit: &Cpu, usage: &Cpu) -> Duration {
+    let remain = (limit.kernel.saturating_sub(usage.kernel))
+        .min(limit.user.saturating_sub(usage.user))
+        .min(limit.total.saturating_sub(usage.total));
+    Duration::from_nanos(remain)
+}
+
+/// cpu time used beyond the limit, it's the delay of enforcement
+fn overshoot(limit: &Cpu, usage: &Cpu) -> Duration {
+    let overshoot = (usage.kernel.saturating_sub(limit.kernel))
+        .max(usage.user.saturating_sub(limit.user))
+        .max(usage.total.saturating_sub(limit.total));
+    Duration::from_nanos(overshoot)
+}
+
+/// check which resource is exhausted
+///
+/// Peak usage reaching the limit doesn't mean exhaustion, because
+/// the process may survive the limit by reclaiming memory, so memory is
+/// only exhausted on oom kill or failed allocation.
+///
+/// Failed allocation may be handled by the process, caller should ignore
+/// [`MonitorKind::Memory`] if the process exits successfully.
+fn exhaust(stat: &impl CgroupStat, cpu: &Cpu) -> Option<MonitorKind> {
+    if stat.oom() {
+        return Some(MonitorKind::OomKill);
+    }
+    if stat.hit_limit() {
+        return Some(MonitorKind::Memory);
+    }
+    if Cpu::out_of_resources(cpu, stat.cpu()) {
+        return Some(MonitorKind::Cpu);
+    }
+    None
+}
+
 /// monitor resource of cpu and memory
 pub struct Monitor {
     cgroup: Arc<Cgroup>,
@@ -62,7 +112,7 @@ impl Monitor {
     /// create a new limiter and mount at given path
     pub fn new((mem, cpu): MemAndCpu) -> Result<Self, Error> {
         let cg_name = format!("mdoj.{}", CG_PATH_COUNTER.fetch_add(1, Ordering::AcqRel));
-        log::trace!("create cgroup, name: {}", cg_name);
+        tracing::trace!("create cgroup, name: {}", cg_name);
         let cgroup = Arc::new(
             CgroupBuilder::new(&cg_name)
                 .memory()
@@ -71,19 +121,17 @@ impl Monitor {
                 .memory_swap_limit(0)
                 .done()
                 .cpu()
-                // .period((MONITOR_ACCURACY / 2).as_nanos() as u64)
-                .quota(MONITOR_ACCURACY.as_nanos() as i64)
-                .realtime_period(MONITOR_ACCURACY.as_nanos() as u64)
-                // .realtime_runtime(MONITOR_ACCURACY.as_nanos() as i64)
+                .period(CPU_PERIOD)
+                .quota(CPU_PERIOD as i64)
                 .done()
                 // .set_specified_controllers(vec!["cpu","memory","pids"].into_iter().map(|x|x.to_string()).collect())
                 .build(MONITER_KIND.heir())?,
         );
-        // FIXME: set oom control
+        // oom killer is left enabled, and kill is reported as [`MonitorKind::OomKill`]
 
         let monitor_task = Some(tokio::spawn(monitor(cgroup.clone(), cpu.clone())));
 
-        log::debug!("cgroup created: {}", cgroup.path());
+        tracing::debug!("cgroup created: {}", cgroup.path());
         Ok(Self {
             cgroup,
             monitor_task,
@@ -103,10 +151,8 @@ impl super::Monitor for Monitor {
     /// the process inside it,
     /// user SHOULD NOT rely on this to kill the process.
     ///
-    ///
-    /// 2. Actively limit(notify) cpu resource is achieved by polling the cgroup,
-    ///     the delay require special attention, it is only guaranteed
-    ///     to below limitation provided + [`MONITOR_ACCURACY`].
+    /// Cpu usage is checked when the remaining budget could be used up,
+    /// it is only guaranteed to below limitation provided + [`MONITOR_ACCURACY`].
     ///
     /// This method is cancellation safe
     async fn wait_exhaust(&mut self) -> MonitorKind {
@@ -114,19 +160,12 @@ impl super::Monitor for Monitor {
     }
     fn poll_exhaust(&mut self) -> Option<MonitorKind> {
         let wrapper = wrapper::CgroupWrapper::new(&self.cgroup);
-
-        if wrapper.oom() {
-            return Some(MonitorKind::Memory);
-        } else if Cpu::out_of_resources(&self.cpu, wrapper.cpu()) {
-            return Some(MonitorKind::Cpu);
-        }
-        None
+        exhaust(&wrapper, &self.cpu)
     }
     /// get the final resource usage
     ///
-    /// Please remember thatActively limit(notify) cpu resource is achieved
-    /// by polling the cgroup, therefore the delay require special attention,
-    /// it is only guaranteed to below limitation provided + [`MONITOR_ACCURACY`].
+    /// Please remember that cpu usage might exceed limitation provided
+    /// by at most [`MONITOR_ACCURACY`].
     async fn stat(self) -> Self::Resource {
         // FIXME: check running process, this line is commented out because of uncollected process
         // uncollected process is at state of not running, but pid is still in use
@@ -136,4 +175,84 @@ impl super::Monitor for Monitor {
     }
 }
 
-// FIXME: mock cgroup and test it
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// cgroup with fixed statistics
+    struct FakeCgroup {
+        memory: Memory,
+        cpu: Cpu,
+        oom: bool,
+        hit_limit: bool,
+    }
+
+    impl CgroupStat for FakeCgroup {
+        fn cpu(&self) -> Cpu {
+            self.cpu.clone()
+        }
+        fn memory(&self) -> Memory {
+            self.memory.clone()
+        }
+        fn oom(&self) -> bool {
+            self.oom
+        }
+        fn hit_limit(&self) -> bool {
+            self.hit_limit
+        }
+    }
+
+    fn memory(x: u64) -> Memory {
+        Memory {
+            kernel: x,
+            user: x,
+            total: x,
+        }
+    }
+    fn cpu(x: u64) -> Cpu {
+        Cpu {
+            kernel: x,
+            user: x,
+            total: x,
+        }
+    }
+
+    #[test]
+    fn cpu_budget() {
+        let (limit, usage) = (cpu(1000), cpu(400));
+        assert_eq!(remain(&limit, &usage), Duration::from_nanos(600));
+        assert_eq!(overshoot(&limit, &usage), Duration::ZERO);
+
+        let usage = Cpu {
+            kernel: 100,
+            user: 1200,
+            total: 1300,
+        };
+        assert_eq!(remain(&limit, &usage), Duration::ZERO);
+        assert_eq!(overshoot(&limit, &usage), Duration::from_nanos(300));
+    }
+    #[test]
+    fn exhaust_reason() {
+        let cpu_limit = cpu(1000);
+        let mut stat = FakeCgroup {
+            memory: memory(512),
+            cpu: cpu(500),
+            oom: false,
+            hit_limit: false,
+        };
+        assert_eq!(exhaust(&stat, &cpu_limit), None);
+
+        // peak reaching the limit is not exhaustion
+        stat.memory = memory(1024);
+        assert_eq!(exhaust(&stat, &cpu_limit), None);
+
+        stat.cpu = cpu(1001);
+        assert_eq!(exhaust(&stat, &cpu_limit), Some(MonitorKind::Cpu));
+
+        stat.hit_limit = true;
+        assert_eq!(exhaust(&stat, &cpu_limit), Some(MonitorKind::Memory));
+
+        stat.oom = true;
+        assert_eq!(exhaust(&stat, &cpu_limit), Some(MonitorKind::OomKill));
+    }
+}
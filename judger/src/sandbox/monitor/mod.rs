@@ -34,7 +34,7 @@ pub trait Monitor {
             if let Some(reason) = self.poll_exhaust() {
                 return reason;
             }
-            tokio::time::sleep(Duration::from_millis(12)).await;
+            tokio::time::sleep(mem_cpu::POLL_INTERVAL).await;
         }
     }
     /// poll for exhaust of resource
@@ -51,7 +51,10 @@ pub trait Monitor {
 /// Exit reason of the process
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum MonitorKind {
+    /// allocation failed at the hard limit, and the process doesn't exit successfully
     Memory,
+    /// process is killed by oom killer
+    OomKill,
     Output,
     Walltime,
     Cpu,
@@ -67,6 +70,7 @@ impl Display for MonitorKind {
                 Self::Output => "output limit",
                 Self::Walltime => "wall time",
                 Self::Memory => "memory",
+                Self::OomKill => "oom killer",
             }
         )
     }
@@ -120,6 +120,7 @@ impl Cpu {
         stat.kernel > resource.kernel || stat.user > resource.user || stat.total > resource.total
     }
 
+    /// `cpuacct.usage*` of cgroup v1 is already in nanosecond
     pub(super) fn from_acct(acct: CpuAcct) -> Self {
         Cpu {
             kernel: acct.usage_sys,
@@ -127,16 +128,18 @@ impl Cpu {
             total: acct.usage,
         }
     }
+    /// parse `cpu.stat` of cgroup v2, which is in microsecond
     pub(super) fn from_raw(raw: &str) -> Self {
         let mut kernel = u64::MAX;
         let mut user = u64::MAX;
         let mut total = u64::MAX;
 
         for (key, value) in raw.split('\n').filter_map(|stmt| stmt.split_once(' ')) {
+            let nanos = || value.parse::<u64>().unwrap().saturating_mul(1000);
             match key {
-                "usage_usec" => total = value.parse().unwrap(),
-                "user_usec" => user = value.parse().unwrap(),
-                "system_usec" => kernel = value.parse().unwrap(),
+                "usage_usec" => total = nanos(),
+                "user_usec" => user = nanos(),
+                "system_usec" => kernel = nanos(),
                 _ => {}
             };
         }
@@ -157,8 +160,8 @@ mod test {
     fn cpu_from_raw() {
         let raw = "usage_usec 158972260000\nuser_usec 115998852000\nsystem_usec 42973408000\ncore_sched.force_idle_usec 0\nnr_periods 0\nnr_throttled 0\nthrottled_usec 0\nnr_bursts 0\nburst_usec 0\n";
         let cpu = Cpu::from_raw(raw);
-        assert_eq!(cpu.kernel, 42973408000);
-        assert_eq!(cpu.user, 115998852000);
-        assert_eq!(cpu.total, 158972260000);
+        assert_eq!(cpu.kernel, 42973408000000);
+        assert_eq!(cpu.user, 115998852000000);
+        assert_eq!(cpu.total, 158972260000000);
     }
 }
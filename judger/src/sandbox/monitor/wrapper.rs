@@ -1,7 +1,9 @@
 use crate::async_loop;
 
 use super::{hier::*, stat::*};
-use cgroups_rs::{cpu::CpuController, cpuacct::CpuAcctController, memory::MemController, Cgroup};
+use cgroups_rs::{
+    cpu::CpuController, cpuacct::CpuAcctController, memory::MemController, Cgroup, Controller,
+};
 use std::{ops::Deref, pin::pin, sync::Arc};
 use tokio::{task::JoinHandle, time};
 
@@ -32,20 +34,6 @@ impl<'a> CgroupWrapper<'a> {
     pub fn new(cgroup: &'a Cgroup) -> Self {
         Self(cgroup)
     }
-    /// get cpu usage(statistics)
-    pub fn cpu(&self) -> Cpu {
-        match MONITER_KIND.deref() {
-            MonitorKind::CpuAcct => {
-                let controller: &CpuAcctController = self.0.controller_of().unwrap();
-                Cpu::from_acct(controller.cpuacct())
-            }
-            MonitorKind::Cpu => {
-                let controller: &CpuController = self.0.controller_of().unwrap();
-                let raw: &str = &controller.cpu().stat;
-                Cpu::from_raw(raw)
-            }
-        }
-    }
     /// get an receiver(synchronize) for oom event
     pub fn oom_signal(&self) -> OOMSignal {
         let controller = self.0.controller_of::<MemController>().unwrap();
@@ -63,28 +51,86 @@ impl<'a> CgroupWrapper<'a> {
             }))
         }
     }
-    /// get memory usage(statistics)
-    pub fn memory(&self) -> Memory {
+}
+
+/// statistics of cgroup that monitor depend on
+///
+/// it's a trait so that monitor can be tested without a real cgroup
+pub trait CgroupStat {
+    /// get cpu usage(statistics)
+    fn cpu(&self) -> Cpu;
+    /// get peak memory usage(statistics)
+    fn memory(&self) -> Memory;
+    /// check if any process is killed by oom killer
+    fn oom(&self) -> bool;
+    /// check if memory usage ever hit the limit and failed to reclaim
+    fn hit_limit(&self) -> bool;
+}
+
+impl<'a> CgroupStat for CgroupWrapper<'a> {
+    /// get cpu usage(statistics)
+    fn cpu(&self) -> Cpu {
+        match MONITER_KIND.deref() {
+            MonitorKind::CpuAcct => {
+                let controller: &CpuAcctController = self.0.controller_of().unwrap();
+                Cpu::from_acct(controller.cpuacct())
+            }
+            MonitorKind::Cpu => {
+                let controller: &CpuController = self.0.controller_of().unwrap();
+                let raw: &str = &controller.cpu().stat;
+                Cpu::from_raw(raw)
+            }
+        }
+    }
+    /// get peak memory usage(statistics)
+    ///
+    /// `memory.peak` is used on cgroup v2(`max_usage_in_bytes` is not
+    /// available), fallback to current usage on kernel older than 5.19
+    fn memory(&self) -> Memory {
         let controller = self.0.controller_of::<MemController>().unwrap();
-        let kernel_usage = controller.kmem_stat();
+        let kernel = controller.kmem_stat().max_usage_in_bytes;
 
-        let kernel = kernel_usage.max_usage_in_bytes;
-        let user = controller.memory_stat().max_usage_in_bytes;
-        let total = kernel + user;
+        if self.0.v2() {
+            // kernel memory is charged to `memory.peak` on cgroup v2
+            let user = std::fs::read_to_string(controller.path().join("memory.peak"))
+                .ok()
+                .and_then(|x| x.trim().parse().ok())
+                .unwrap_or_else(|| controller.memory_stat().usage_in_bytes);
+            return Memory {
+                kernel,
+                user,
+                total: user,
+            };
+        }
 
+        let user = controller.memory_stat().max_usage_in_bytes;
         Memory {
             kernel,
             user,
-            total,
+            total: kernel + user,
         }
     }
     /// check if oom
     ///
-    /// use [`oom_signal`] if long polling is required
-    pub fn oom(&self) -> bool {
+    /// use [`CgroupWrapper::oom_signal`] if long polling is required
+    fn oom(&self) -> bool {
         let controller: &MemController = self.0.controller_of().unwrap();
         controller.memory_stat().oom_control.oom_kill != 0
     }
+    /// check `max` of `memory.events` on cgroup v2, `memory.failcnt` on cgroup v1
+    fn hit_limit(&self) -> bool {
+        let controller: &MemController = self.0.controller_of().unwrap();
+        if self.0.v2() {
+            return std::fs::read_to_string(controller.path().join("memory.events"))
+                .ok()
+                .and_then(|x| {
+                    x.lines()
+                        .find_map(|line| line.strip_prefix("max ")?.trim().parse::<u64>().ok())
+                })
+                .is_some_and(|x| x != 0);
+        }
+        controller.memory_stat().fail_cnt != 0
+    }
 }
 
 /// newtype wrapper for cgroup form cgroup_rs
@@ -111,7 +157,7 @@ impl CgroupWrapperOwned {
         tokio::spawn(async_loop!({
             self.0.delete().ok();
             // it's rare case, but we should react to it if it happens frequently
-            log::debug!("cgroup delete failed, retrying...");
+            tracing::debug!("cgroup delete failed, retrying...");
             time::sleep(time::Duration::from_nanos(1)).await;
         }));
         // FIXME: busy waiting with std::hint::spin_loop(check Arc::strong_count)
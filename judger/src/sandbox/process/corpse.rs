@@ -1,4 +1,4 @@
-use std::process::ExitStatus;
+use std::{os::unix::process::ExitStatusExt, process::ExitStatus};
 
 use super::monitor::{MonitorKind, Stat};
 
@@ -10,6 +10,8 @@ pub struct Corpse {
     /// exit reason reported by monitor
     pub(super) reason: Option<MonitorKind>,
     pub(super) stdout: Vec<u8>,
+    /// truncated stderr
+    pub(super) stderr: Vec<u8>,
     pub(super) stat: Stat,
 }
 
@@ -25,6 +27,28 @@ impl Corpse {
             Ok(self.code.unwrap())
         }
     }
+    /// get the exit code of the process
+    ///
+    /// return `None` if the process is killed by signal or monitor
+    pub fn exit_code(&self) -> Option<i32> {
+        let code = self.status().ok()?.code()?;
+        match self.signal() {
+            Some(_) => None,
+            None => Some(code),
+        }
+    }
+    /// get the signal terminating the process
+    ///
+    /// nsjail report child killed by signal as exit code `128 + signal`,
+    /// return `None` if the process is killed by monitor
+    pub fn signal(&self) -> Option<i32> {
+        let status = self.status().ok()?;
+        match status.code() {
+            Some(code) if code > 128 && code <= 128 + 64 => Some(code - 128),
+            Some(_) => None,
+            None => status.signal(),
+        }
+    }
     /// get the stdout of the process
     ///
     /// If the process is killed by resource limit mechanism,
@@ -32,6 +56,10 @@ impl Corpse {
     pub fn stdout(&self) -> &[u8] {
         &self.stdout
     }
+    /// get the stderr of the process, truncated at the limit
+    pub fn stderr(&self) -> &[u8] {
+        &self.stderr
+    }
     /// get the resource usage of the process
     pub fn stat(&self) -> &Stat {
         &self.stat
@@ -40,3 +68,31 @@ impl Corpse {
         self.reason.is_none() && self.code.is_some() && self.code.unwrap().success()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn corpse(raw: i32) -> Corpse {
+        Corpse {
+            code: Some(ExitStatus::from_raw(raw)),
+            reason: None,
+            stdout: Vec::new(),
+            stderr: Vec::new(),
+            stat: Stat::default(),
+        }
+    }
+
+    #[test]
+    fn exit_status() {
+        // exit(1)
+        let x = corpse(1 << 8);
+        assert_eq!((x.exit_code(), x.signal()), (Some(1), None));
+        // killed by SIGSEGV
+        let x = corpse(libc::SIGSEGV);
+        assert_eq!((x.exit_code(), x.signal()), (None, Some(libc::SIGSEGV)));
+        // nsjail exit with 128 + SIGFPE
+        let x = corpse((128 + libc::SIGFPE) << 8);
+        assert_eq!((x.exit_code(), x.signal()), (None, Some(libc::SIGFPE)));
+    }
+}
@@ -3,12 +3,30 @@ use std::{
     ffi::{OsStr, OsString},
     path::PathBuf,
     process::Stdio,
+    time::Instant,
 };
 use tokio::{
-    io::{self, AsyncWriteExt, DuplexStream},
+    io::{self, AsyncRead, AsyncReadExt, AsyncWriteExt, DuplexStream},
     process::*,
+    sync::mpsc,
     time,
 };
+/// read at most `limit` bytes from `reader`
+///
+/// Unlike output limit, exceeding it doesn't kill the process,
+/// the rest is drained so the process won't block on a full pipe.
+async fn capture<R: AsyncRead + Unpin>(reader: R, limit: u64) -> Vec<u8> {
+    let mut buffer = Vec::new();
+    let mut reader = reader.take(limit);
+    if let Err(err) = reader.read_to_end(&mut buffer).await {
+        tracing::debug!("Fail capturing stderr: {}", err);
+    }
+    io::copy(&mut reader.into_inner(), &mut io::sink())
+        .await
+        .ok();
+    buffer
+}
+
 /// A not yet launched process that is mounted with a filesystem
 struct MountedProcess<C: Context> {
     context: C,
@@ -96,12 +114,11 @@ impl<C: Context> Process<C> {
         cmd.kill_on_drop(true);
         cmd.stdin(Stdio::piped());
         cmd.stdout(Stdio::piped());
-        #[cfg(not(debug_assertions))]
-        cmd.stderr(Stdio::null());
-        #[cfg(debug_assertions)]
-        cmd.stderr(Stdio::inherit());
+        cmd.stderr(Stdio::piped());
         cmd.env("PATH", self.get_env());
 
+        let bind_ro = self.fs.get_bind_ro().to_vec();
+
         let arg_factory = ArgFactory::default()
             .add(BaseArg)
             .add(CGroupVersionArg)
@@ -110,6 +127,7 @@ impl<C: Context> Process<C> {
             })
             .add(MountArg {
                 rootfs: self.fs.get_path().as_ref(),
+                bind_ro: &bind_ro,
             })
             .add(InnerProcessArg {
                 inner_args: self.context.get_args(),
@@ -117,23 +135,85 @@ impl<C: Context> Process<C> {
 
         let args = arg_factory.build();
 
-        log::trace!("spawn process with args: {:?}", args);
+        tracing::trace!("spawn process with args: {:?}", args);
         cmd.args(args);
 
         Ok(cmd.spawn()?)
     }
     /// spawn a process and wait for it to finish
-    pub async fn wait(mut self, input: Vec<u8>) -> Result<Corpse, Error> {
+    pub async fn wait(self, input: Vec<u8>) -> Result<Corpse, Error> {
+        self.wait_inner(input, None, None).await
+    }
+    /// like [`Process::wait`], but also send stdout to `tx` as it's produced
+    ///
+    /// Output sent is truncated at output limit, same as [`Corpse::stdout`]
+    pub async fn wait_with_stream(
+        self,
+        input: Vec<u8>,
+        tx: mpsc::Sender<Vec<u8>>,
+    ) -> Result<Corpse, Error> {
+        self.wait_inner(input, None, Some(tx)).await
+    }
+    /// like [`Process::wait_with_stream`], but keep stdin open
+    ///
+    /// chunks from `stdin` are written after `input`, stdin is closed
+    /// when `stdin` is closed, and walltime limit is the hard cap
+    pub async fn wait_interactive(
+        self,
+        input: Vec<u8>,
+        stdin: mpsc::Receiver<Vec<u8>>,
+        tx: mpsc::Sender<Vec<u8>>,
+    ) -> Result<Corpse, Error> {
+        self.wait_inner(input, Some(stdin), Some(tx)).await
+    }
+    async fn wait_inner(
+        mut self,
+        input: Vec<u8>,
+        rx: Option<mpsc::Receiver<Vec<u8>>>,
+        tx: Option<mpsc::Sender<Vec<u8>>>,
+    ) -> Result<Corpse, Error> {
+        let start = Instant::now();
         let mut process = self.spawn_raw_process()?;
+        crate::metrics::sandbox_setup(start.elapsed());
 
         let mut stdin = process.stdin.take().unwrap();
-        tokio::spawn(async move { stdin.write_all(&input).await });
+        tokio::spawn(async move {
+            stdin.write_all(&input).await?;
+            if let Some(mut rx) = rx {
+                while let Some(chunk) = rx.recv().await {
+                    stdin.write_all(&chunk).await?;
+                }
+            }
+            io::Result::Ok(())
+        });
 
+        let stderr = process.stderr.take().unwrap();
+        let stderr = tokio::spawn(capture(stderr, self.context.get_stderr()));
+
+        let mut remain = self.context.get_output() as usize;
         let stdout = process.stdout.take().unwrap();
         let io_proxy = tokio::spawn(async move {
             let mut stdout = stdout;
-            if let Err(err) = io::copy(&mut stdout, &mut self.stdout).await {
-                log::debug!("Fail forwarding buffer: {}", err);
+            let mut buf = vec![0_u8; 4096];
+            loop {
+                let size = match stdout.read(&mut buf).await {
+                    Ok(0) => break,
+                    Ok(x) => x,
+                    Err(err) => {
+                        tracing::debug!("Fail reading stdout: {}", err);
+                        break;
+                    }
+                };
+                if let Some(tx) = tx.as_ref().filter(|_| remain > 0) {
+                    let chunk = buf[..size.min(remain)].to_vec();
+                    remain -= chunk.len();
+                    // receiver may be gone, but the process should still be monitored
+                    tx.send(chunk).await.ok();
+                }
+                if let Err(err) = self.stdout.write_all(&buf[..size]).await {
+                    tracing::debug!("Fail forwarding buffer: {}", err);
+                    break;
+                }
             }
         });
 
@@ -145,14 +225,24 @@ impl<C: Context> Process<C> {
                 Some(x?)
             }
         };
+        if code.is_none() {
+            // resource exhausted, don't wait for stderr of a running process
+            process.start_kill().ok();
+        }
         // wait for the proxy to finish for full output
         // in case of OLE, the monitor will drop and the proxy will be cancelled(yield)
         io_proxy.await.unwrap();
 
+        let reason = match monitor.poll_exhaust() {
+            // the process handled failed allocation by itself
+            Some(MonitorKind::Memory) if code.as_ref().is_some_and(|x| x.success()) => None,
+            x => x,
+        };
         Ok(Corpse {
             code,
-            reason: monitor.poll_exhaust(),
+            reason,
             stdout: monitor.take_buffer(),
+            stderr: stderr.await.unwrap(),
             stat: monitor.stat().await,
         })
     }
@@ -3,7 +3,7 @@ use std::{
     ffi::{OsStr, OsString},
     ops::Deref,
     os::unix::ffi::OsStrExt,
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use crate::CONFIG;
@@ -50,8 +50,8 @@ impl Argument for BaseArg {
             )),
         ];
 
-        if !CONFIG.rootless {
-            log::debug!("running in root mode");
+        if !CONFIG.get().rootless {
+            tracing::debug!("running in root mode");
             args.push(Cow::Borrowed(OsStr::from_bytes(b"--disable_clone_newuser")));
         }
         args.into_iter()
@@ -107,18 +107,27 @@ impl Argument for CGroupVersionArg {
 /// arguments for rootfs mount
 pub struct MountArg<'a> {
     pub rootfs: &'a Path,
+    /// read-only bind mounts as `(source, target)`
+    pub bind_ro: &'a [(PathBuf, PathBuf)],
 }
 
 impl<'a> Argument for MountArg<'a> {
     fn get_args(self) -> impl Iterator<Item = Cow<'static, OsStr>> {
-        vec![
+        let mut args = vec![
             Cow::Borrowed(OsStr::from_bytes(b"--tmpfsmount")),
             Cow::Borrowed(OsStr::from_bytes(b"/tmp")),
             Cow::Borrowed(OsStr::from_bytes(b"--rw")),
             Cow::Borrowed(OsStr::from_bytes(b"--chroot")),
             Cow::Owned(OsString::from(self.rootfs)),
-        ]
-        .into_iter()
+        ];
+        for (source, target) in self.bind_ro {
+            let mut arg = source.as_os_str().to_owned();
+            arg.push(":");
+            arg.push(target);
+            args.push(Cow::Borrowed(OsStr::from_bytes(b"--bindmount_ro")));
+            args.push(Cow::Owned(arg));
+        }
+        args.into_iter()
     }
 }
 
@@ -0,0 +1,257 @@
+//! `judger selftest`, check if the host enforce limits of sandbox
+//!
+//! Adversarial programs are run through the real [`Process`] path,
+//! inside rootfs of a plugin, so cgroup, nsjail and filesystem are all
+//! exercised. Programs are shell scripts, the plugin should provide a
+//! busybox-like `/bin/sh`.
+use std::{
+    ffi::{OsStr, OsString},
+    path::Path,
+    time::Duration,
+};
+
+use tokio::{net::TcpStream, time};
+use uuid::Uuid;
+
+use crate::{
+    filesystem::{MountView, Rootfs},
+    language::{Plugin, PluginMap},
+    sandbox::{Context, Corpse, Cpu, Limit, Memory, MonitorKind, Process},
+    server::PLUGIN_PATH,
+};
+
+const CPU_LIMIT: u64 = 1_000_000_000;
+const MEMORY_LIMIT: u64 = 64 * 1024 * 1024;
+const OUTPUT_LIMIT: u64 = 1024 * 1024;
+const WALLTIME_LIMIT: Duration = Duration::from_secs(3);
+/// file written by [`Expect::Isolated`] case, relative to root of sandbox
+const ESCAPE_FILE: &str = "selftest-escape";
+
+/// expected outcome of a case
+enum Expect {
+    /// killed by one of the monitors
+    Killed(&'static [MonitorKind]),
+    /// killed by any monitor
+    AnyKilled,
+    /// connecting to the address fails while it succeeds on host,
+    /// script exit with code 1 on failure, 2 if there is no tool to connect
+    Unreachable(&'static str),
+    /// file written outside `/tmp` is denied, or visible to neither host
+    /// nor other sandboxes
+    Isolated,
+    /// writing files endlessly fails before filling the disk, script exit
+    /// with code 1 on failure, memory limit also count since tmpfs is charged
+    /// to cgroup
+    WriteBounded,
+}
+
+struct Case {
+    name: &'static str,
+    script: &'static str,
+    expect: Expect,
+}
+
+const CASES: &[Case] = &[
+    Case {
+        name: "fork bomb",
+        script: "f(){ f|f& }; f",
+        expect: Expect::AnyKilled,
+    },
+    Case {
+        name: "memory hog",
+        script: "x=a; while :; do x=$x$x; done",
+        expect: Expect::Killed(&[MonitorKind::Memory, MonitorKind::OomKill]),
+    },
+    Case {
+        name: "infinite loop",
+        script: "while :; do :; done",
+        expect: Expect::Killed(&[MonitorKind::Cpu]),
+    },
+    Case {
+        name: "sleep forever",
+        script: "sleep 3600",
+        expect: Expect::Killed(&[MonitorKind::Walltime]),
+    },
+    Case {
+        name: "output flood",
+        script: "while :; do echo 0123456789abcdef; done",
+        expect: Expect::Killed(&[MonitorKind::Output]),
+    },
+    Case {
+        name: "network access",
+        script: "if command -v nc >/dev/null; then \
+                 nc -w 1 1.1.1.1 53 </dev/null && exit 0; exit 1; fi; \
+                 [ -n \"$BASH_VERSION\" ] || exit 2; \
+                 (exec 3<>/dev/tcp/1.1.1.1/53) && exit 0; exit 1",
+        expect: Expect::Unreachable("1.1.1.1:53"),
+    },
+    Case {
+        name: "write outside tmp",
+        script: "echo escaped > /selftest-escape",
+        expect: Expect::Isolated,
+    },
+    Case {
+        name: "fill disk",
+        script: "i=0; while [ $i -lt 4096 ]; do \
+                 head -c 1048576 /dev/zero > /selftest-fill-$i || exit 1; \
+                 i=$((i+1)); done; exit 0",
+        expect: Expect::WriteBounded,
+    },
+];
+
+enum Outcome {
+    Pass,
+    Fail(String),
+    Skip(&'static str),
+}
+
+/// Process context for self test
+struct SelfTestCtx {
+    fs: MountView,
+    args: [OsString; 3],
+}
+
+impl SelfTestCtx {
+    fn new(fs: MountView, script: &str) -> Self {
+        Self {
+            fs,
+            args: ["/bin/sh".into(), "-c".into(), script.into()],
+        }
+    }
+}
+
+impl Limit for SelfTestCtx {
+    fn get_cpu(&mut self) -> Cpu {
+        Cpu {
+            kernel: CPU_LIMIT,
+            user: CPU_LIMIT,
+            total: CPU_LIMIT,
+        }
+    }
+    fn get_memory(&mut self) -> Memory {
+        Memory {
+            kernel: MEMORY_LIMIT,
+            user: MEMORY_LIMIT,
+            total: MEMORY_LIMIT,
+        }
+    }
+    fn get_output(&mut self) -> u64 {
+        OUTPUT_LIMIT
+    }
+    fn get_walltime(&mut self) -> Duration {
+        WALLTIME_LIMIT
+    }
+}
+
+impl Context for SelfTestCtx {
+    type FS = MountView;
+    fn get_fs(&mut self) -> Self::FS {
+        self.fs.clone()
+    }
+    fn get_args(&mut self) -> impl Iterator<Item = &OsStr> {
+        self.args.iter().map(|x| x.as_os_str())
+    }
+}
+
+fn check(corpse: &Corpse, expect: &Expect) -> Outcome {
+    match (corpse.status(), expect) {
+        (Err(reason), Expect::Killed(kinds)) if kinds.contains(&reason) => Outcome::Pass,
+        (Err(_), Expect::AnyKilled) => Outcome::Pass,
+        (Err(MonitorKind::Memory | MonitorKind::OomKill), Expect::WriteBounded) => Outcome::Pass,
+        (Err(reason), _) => Outcome::Fail(format!("killed by {} monitor", reason)),
+        (Ok(_), Expect::Unreachable(_)) => match corpse.exit_code() {
+            Some(1) => Outcome::Pass,
+            Some(2) => Outcome::Skip("not applicable in the rootfs"),
+            Some(0) => Outcome::Fail("connection is not denied".to_string()),
+            code => Outcome::Fail(format!("unexpected exit code {:?}", code)),
+        },
+        (Ok(_), Expect::Isolated) => Outcome::Pass,
+        (Ok(_), Expect::WriteBounded) => match corpse.exit_code() {
+            Some(1) => Outcome::Pass,
+            Some(0) => Outcome::Fail("4GiB is written without error".to_string()),
+            code => Outcome::Fail(format!("unexpected exit code {:?}", code)),
+        },
+        (Ok(_), _) => Outcome::Fail(format!(
+            "exited({:?}) without being killed",
+            corpse.exit_code()
+        )),
+    }
+}
+
+async fn run_case(plugin: &Plugin<Rootfs>, case: &Case) -> crate::Result<Outcome> {
+    let host = Path::new("/").join(ESCAPE_FILE);
+    match case.expect {
+        Expect::Unreachable(addr) => {
+            let connect = time::timeout(Duration::from_secs(1), TcpStream::connect(addr));
+            if !matches!(connect.await, Ok(Ok(_))) {
+                return Ok(Outcome::Skip("host is unable to connect either"));
+            }
+        }
+        Expect::Isolated => {
+            tokio::fs::remove_file(&host).await.ok();
+        }
+        _ => {}
+    }
+
+    let handle = plugin.mount(Vec::new()).await?;
+    let ctx = SelfTestCtx::new(handle.get_view(), case.script);
+    let corpse = Process::new(ctx)?.wait(Vec::new()).await?;
+    let outcome = check(&corpse, &case.expect);
+    if let (Outcome::Pass, Expect::Isolated) = (&outcome, &case.expect) {
+        if host.exists() {
+            return Ok(Outcome::Fail("write reached host".to_string()));
+        }
+        if !handle.get_path().join(ESCAPE_FILE).exists() {
+            // write is denied
+            return Ok(Outcome::Pass);
+        }
+        // write should only be visible in its own sandbox
+        drop(handle);
+        let handle = plugin.mount(Vec::new()).await?;
+        if handle.get_path().join(ESCAPE_FILE).exists() {
+            return Ok(Outcome::Fail(
+                "write is visible to other sandbox".to_string(),
+            ));
+        }
+    }
+    Ok(outcome)
+}
+
+/// run all cases, return true if none fail
+///
+/// use rootfs of plugin with `lang` uuid, or the first plugin if not set
+pub async fn run(lang: Option<String>) -> bool {
+    let plugins = match PluginMap::new(PLUGIN_PATH).await {
+        Ok(x) => x,
+        Err(err) => {
+            println!("FAIL  load plugins: {}", err);
+            return false;
+        }
+    };
+    let plugin = match lang {
+        Some(lang) => Uuid::parse_str(&lang).ok().and_then(|x| plugins.get(&x)),
+        None => plugins.iter().next().cloned(),
+    };
+    let Some(plugin) = plugin else {
+        println!("FAIL  no plugin to provide rootfs");
+        return false;
+    };
+    println!("using rootfs of {}", plugin.get_info().lang_name);
+
+    let mut success = true;
+    for case in CASES {
+        match run_case(&plugin, case).await {
+            Ok(Outcome::Pass) => println!("PASS  {}", case.name),
+            Ok(Outcome::Skip(reason)) => println!("SKIP  {}: {}", case.name, reason),
+            Ok(Outcome::Fail(reason)) => {
+                success = false;
+                println!("FAIL  {}: {}", case.name, reason);
+            }
+            Err(err) => {
+                success = false;
+                println!("FAIL  {}: {}", case.name, err);
+            }
+        }
+    }
+    success
+}
@@ -1,52 +1,179 @@
-use std::{pin::Pin, str::FromStr, sync::Arc};
+use std::{cmp::Ordering, pin::Pin, str::FromStr, sync::Arc};
 
 use async_stream::try_stream;
 use futures_core::Stream;
 use grpc::judger::{judger_server::*, *};
-use tokio::{fs::File, sync::Semaphore};
+use opentelemetry::metrics::{ObservableGauge, Unit};
+use tokio::sync::{mpsc, Semaphore};
 use tokio_stream::StreamExt;
-use tonic::{Request, Response, Status};
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{instrument, Span};
 use uuid::Uuid;
 
 use crate::{
     error::{ClientError, Error},
+    filesystem::Rootfs,
     language::{ExecuteArgBuilder, JudgeArgBuilder, PluginMap},
+    logger::set_remote_parent,
+    metrics::meter,
     CONFIG,
 };
 
-const PLUGIN_PATH: &str = "plugins";
+pub const PLUGIN_PATH: &str = "plugins";
+
+/// stream of execution result, shared by `Exec` and `ExecInteractive`
+type ExecResultStream = Pin<Box<dyn Stream<Item = Result<ExecResult, Status>> + Send>>;
+
+/// compare two byte strings, taking time independent of their content
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
 
 fn check_secret<T>(req: Request<T>) -> Result<T, Status> {
     let (meta, _, payload) = req.into_parts();
-    if CONFIG.secret.is_none() {
+    let config = CONFIG.get();
+    if config.secret.is_none() {
         return Ok(payload);
     }
-    let secret = CONFIG.secret.as_ref().unwrap();
+    let secret = config.secret.as_ref().unwrap();
     if let Some(header) = meta.get("Authorization") {
         let secret = ["basic ", secret].concat().into_bytes();
-        let valid = header
-            .as_bytes()
-            .iter()
-            .zip(secret.iter())
-            .map(|(&a, &b)| a == b)
-            .reduce(|a, b| a && b);
-        if valid.unwrap_or(false) {
+        if constant_time_eq(header.as_bytes(), &secret) {
             return Ok(payload);
         }
     }
     Err(Status::permission_denied("Invalid secret"))
 }
 
+/// check file name of input/output file, empty name is treated as unset
+///
+/// Only plain file name is allowed, so it cannot escape root of sandbox
+fn check_file_name(name: Option<String>) -> Result<Option<String>, ClientError> {
+    match name {
+        Some(x) if x.is_empty() => Ok(None),
+        Some(x) if x == "." || x == ".." || x.contains(['/', '\0']) => {
+            Err(ClientError::InvalidFileName)
+        }
+        x => Ok(x),
+    }
+}
+
 pub struct Server {
     semaphore: Arc<Semaphore>,
-    plugins: PluginMap<File>,
+    plugins: PluginMap<Rootfs>,
+    _reserved: ObservableGauge<u64>,
 }
 
 impl Server {
     pub async fn new() -> crate::Result<Server> {
-        let semaphore = Arc::new(Semaphore::new(CONFIG.memory.try_into().unwrap()));
+        let semaphore = Arc::new(Semaphore::new(CONFIG.get().memory.try_into().unwrap()));
         let plugins = PluginMap::new(PLUGIN_PATH).await?;
-        Ok(Server { semaphore, plugins })
+
+        let permits = semaphore.clone();
+        let _reserved = meter()
+            .u64_observable_gauge("memory.reserved")
+            .with_description("memory reserved by running sandboxes")
+            .with_unit(Unit::new("By"))
+            .with_callback(move |observer| {
+                observer.observe(
+                    CONFIG
+                        .get()
+                        .memory
+                        .saturating_sub(permits.available_permits() as u64),
+                    &[],
+                )
+            })
+            .init();
+
+        Ok(Server {
+            semaphore,
+            plugins,
+            _reserved,
+        })
+    }
+    /// get handle to resize memory pool at runtime
+    pub fn memory_pool(&self) -> MemoryPool {
+        MemoryPool(self.semaphore.clone())
+    }
+    /// compile and run the code, stream the output back
+    ///
+    /// stdin is kept open for `stdin` if set
+    async fn execute(
+        &self,
+        payload: ExecRequest,
+        stdin: Option<mpsc::Receiver<Vec<u8>>>,
+    ) -> Result<Response<ExecResultStream>, Status> {
+        let memory = payload.memory;
+        let cpu = payload.time;
+
+        let source = payload.code;
+        let input = payload.input;
+
+        let uuid =
+            Uuid::from_str(&payload.lang_uid).map_err(|_| ClientError::InvalidLanguageUuid)?;
+
+        let plugin = self
+            .plugins
+            .get(&uuid)
+            .ok_or(ClientError::InvalidLanguageUuid)?;
+
+        let resource: u32 = plugin
+            .get_memory_reserved(payload.memory)
+            .try_into()
+            .map_err(|_| Error::Platform)?;
+
+        let permit = self
+            .semaphore
+            .clone()
+            .acquire_many_owned(resource)
+            .await
+            .map_err(|_| ClientError::ImpossibleMemoryRequirement)?;
+
+        let mut args = ExecuteArgBuilder::new()
+            .cpu(cpu)
+            .mem(memory)
+            .source(source)
+            .input(input);
+        if let Some(stdin) = stdin {
+            args = args.stdin(stdin);
+        }
+
+        let mut result = plugin.execute(args.build()).await;
+
+        Ok(Response::new(Box::pin(try_stream! {
+            while let Some(r) = result.next().await {
+                yield ExecResult::from(r?);
+            }
+            drop(permit);
+        })))
+    }
+}
+
+/// memory pool shared by all sandboxes, in bytes
+pub struct MemoryPool(Arc<Semaphore>);
+
+impl MemoryPool {
+    /// resize memory pool from `old` to `new`
+    ///
+    /// Shrinking doesn't affect running sandboxes,
+    /// memory is taken from the pool once they release it.
+    pub fn resize(&self, old: u64, new: u64) {
+        match new.cmp(&old) {
+            Ordering::Greater => self.0.add_permits((new - old) as usize),
+            Ordering::Less => {
+                let semaphore = self.0.clone();
+                let shrink = u32::try_from(old - new).unwrap_or(u32::MAX);
+                tokio::spawn(async move {
+                    if let Ok(permit) = semaphore.acquire_many_owned(shrink).await {
+                        permit.forget();
+                    }
+                });
+            }
+            Ordering::Equal => {}
+        }
     }
 }
 
@@ -54,17 +181,32 @@ impl Server {
 impl Judger for Server {
     type JudgeStream = Pin<Box<dyn Stream<Item = Result<JudgeResponse, Status>> + Send>>;
 
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.judger.Judger/judge",
+        err(level = "debug", Display),
+        fields(lang = tracing::field::Empty)
+    )]
     async fn judge(
         &self,
         req: Request<JudgeRequest>,
     ) -> Result<Response<Self::JudgeStream>, Status> {
+        set_remote_parent(&Span::current(), req.metadata());
         let payload = check_secret(req)?;
+        Span::current().record("lang", payload.lang_uid.as_str());
+        tracing::info!(
+            monotonic_counter.judge = 1,
+            lang = payload.lang_uid.as_str()
+        );
 
         let memory = payload.memory;
         let cpu = payload.time;
         let source = payload.code;
         let uuid =
             Uuid::from_str(&payload.lang_uid).map_err(|_| ClientError::InvalidLanguageUuid)?;
+        let input_file = check_file_name(payload.input_file)?;
+        let output_file = check_file_name(payload.output_file)?;
 
         let plugin = self
             .plugins
@@ -94,6 +236,7 @@ impl Judger for Server {
             .output(output.into_iter())
             .mode(payload.rule.into())
             .source(source)
+            .io_file(input_file, output_file)
             .build();
 
         let mut result = plugin.judge(args).await;
@@ -106,7 +249,14 @@ impl Judger for Server {
         })))
     }
 
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.judger.Judger/judger_info",
+        err(level = "debug", Display)
+    )]
     async fn judger_info(&self, req: Request<()>) -> Result<Response<JudgeInfo>, Status> {
+        set_remote_parent(&Span::current(), req.metadata());
         check_secret(req)?;
         let list = self
             .plugins
@@ -114,53 +264,96 @@ impl Judger for Server {
             .map(|v| v.get_info().clone())
             .collect::<Vec<_>>();
         Ok(Response::new(JudgeInfo {
-            memory: CONFIG.memory,
+            memory: CONFIG.get().memory,
             accuracy: 0, // FIXME: accuracy
             langs: Langs { list },
-            cpu_factor: CONFIG.ratio.cpu as f32,
+            cpu_factor: CONFIG.get().ratio.cpu as f32,
         }))
     }
 
-    type ExecStream = tokio_stream::Once<Result<ExecResult, Status>>;
+    type ExecStream = ExecResultStream;
 
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.judger.Judger/exec",
+        err(level = "debug", Display),
+        fields(lang = tracing::field::Empty)
+    )]
     async fn exec(&self, req: Request<ExecRequest>) -> Result<Response<Self::ExecStream>, Status> {
+        set_remote_parent(&Span::current(), req.metadata());
         let payload = check_secret(req)?;
+        Span::current().record("lang", payload.lang_uid.as_str());
+        tracing::info!(monotonic_counter.exec = 1, lang = payload.lang_uid.as_str());
 
-        let memory = payload.memory;
-        let cpu = payload.time;
+        self.execute(payload, None).await
+    }
 
-        let source = payload.code;
-        let input = payload.input;
+    type ExecInteractiveStream = ExecResultStream;
 
-        let uuid =
-            Uuid::from_str(&payload.lang_uid).map_err(|_| ClientError::InvalidLanguageUuid)?;
+    #[instrument(
+        skip_all,
+        level = "info",
+        name = "oj.judger.Judger/exec_interactive",
+        err(level = "debug", Display),
+        fields(lang = tracing::field::Empty)
+    )]
+    async fn exec_interactive(
+        &self,
+        req: Request<Streaming<ExecInput>>,
+    ) -> Result<Response<Self::ExecInteractiveStream>, Status> {
+        set_remote_parent(&Span::current(), req.metadata());
+        let mut stream = check_secret(req)?;
+        let payload = match stream.next().await.transpose()? {
+            Some(ExecInput {
+                request: Some(exec_input::Request::Start(x)),
+            }) => x,
+            _ => return Err(ClientError::MissingExecStart.into()),
+        };
+        Span::current().record("lang", payload.lang_uid.as_str());
+        tracing::info!(
+            monotonic_counter.exec_interactive = 1,
+            lang = payload.lang_uid.as_str()
+        );
 
-        let plugin = self
-            .plugins
-            .get(&uuid)
-            .ok_or(ClientError::InvalidLanguageUuid)?;
+        let (tx, rx) = mpsc::channel(16);
+        tokio::spawn(async move {
+            while let Some(Ok(input)) = stream.next().await {
+                if let Some(exec_input::Request::Stdin(chunk)) = input.request {
+                    if tx.send(chunk).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        });
 
-        let resource: u32 = plugin
-            .get_memory_reserved(payload.memory)
-            .try_into()
-            .map_err(|_| Error::Platform)?;
+        self.execute(payload, Some(rx)).await
+    }
+}
 
-        let permit = self
-            .semaphore
-            .clone()
-            .acquire_many_owned(resource)
-            .await
-            .map_err(|_| ClientError::ImpossibleMemoryRequirement)?;
+#[cfg(test)]
+mod test {
+    use super::{check_file_name, constant_time_eq};
 
-        let args = ExecuteArgBuilder::new()
-            .cpu(cpu)
-            .mem(memory)
-            .source(source)
-            .input(input)
-            .build();
+    #[test]
+    fn secret_compare() {
+        assert!(constant_time_eq(b"basic secret", b"basic secret"));
+        assert!(!constant_time_eq(b"basic sec", b"basic secret"));
+        assert!(!constant_time_eq(b"basic secret!", b"basic secret"));
+        assert!(!constant_time_eq(b"basic secreT", b"basic secret"));
+        assert!(!constant_time_eq(b"", b"basic secret"));
+    }
 
-        let result = plugin.execute(args).await?;
-        drop(permit);
-        Ok(Response::new(tokio_stream::once(Ok(result.into()))))
+    #[test]
+    fn file_name() {
+        assert_eq!(check_file_name(None).unwrap(), None);
+        assert_eq!(check_file_name(Some("".to_string())).unwrap(), None);
+        assert_eq!(
+            check_file_name(Some("output.txt".to_string())).unwrap(),
+            Some("output.txt".to_string())
+        );
+        assert!(check_file_name(Some("..".to_string())).is_err());
+        assert!(check_file_name(Some("../etc/passwd".to_string())).is_err());
+        assert!(check_file_name(Some("/output.txt".to_string())).is_err());
     }
 }
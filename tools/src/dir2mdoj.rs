@@ -0,0 +1,129 @@
+//! convert directory of `*.in/*.out` files to native package
+//!
+//! `x.in` is paired with `x.out` (or `x.ans`), testcases are ordered by
+//! name with numbers compared by value, so `2.in` comes before `10.in`.
+use std::{cmp::Ordering, fs, fs::File, path::PathBuf};
+
+use crate::{
+    grpc::{self, problem_package},
+    package::{self, MatchRule, Package},
+};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+
+#[derive(Debug, Parser)]
+pub struct Dir2mdoj {
+    /// directory of testcases
+    #[arg(long)]
+    dir: PathBuf,
+    /// path of the package to write
+    #[arg(long)]
+    output: PathBuf,
+
+    #[arg(long)]
+    title: String,
+    /// statement in markdown
+    #[arg(long)]
+    statement: Option<PathBuf>,
+    /// in millisecond
+    #[arg(long, default_value_t = 1000)]
+    time: u64,
+    /// in MiB
+    #[arg(long, default_value_t = 256)]
+    memory: u64,
+    #[arg(long, value_enum, default_value_t = MatchRule::IgnoreSnl)]
+    match_rule: MatchRule,
+    #[arg(long)]
+    tags: Vec<String>,
+    /// split evenly across testcases
+    #[arg(long, default_value_t = 100)]
+    total_score: u32,
+}
+
+pub fn dir2mdoj(v: Dir2mdoj) -> Result<()> {
+    let mut names = fs::read_dir(&v.dir)?
+        .map(|x| Ok(x?.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .filter_map(|x| x.strip_suffix(".in").map(ToString::to_string))
+        .collect::<Vec<_>>();
+    names.sort_by(|a, b| natural_cmp(a, b));
+
+    if names.is_empty() {
+        return Err(anyhow!("no `*.in` file in {}", v.dir.display()));
+    }
+
+    let scores = package::even_score(v.total_score, names.len());
+    let testcases = names
+        .iter()
+        .zip(scores)
+        .map(|(name, score)| {
+            let output = ["out", "ans"]
+                .iter()
+                .map(|ext| v.dir.join(format!("{name}.{ext}")))
+                .find(|x| x.is_file())
+                .ok_or_else(|| anyhow!("no output for {name}.in"))?;
+            Ok(problem_package::Testcase {
+                input: fs::read(v.dir.join(format!("{name}.in")))?,
+                output: fs::read(output)?,
+                score,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let content = match &v.statement {
+        Some(path) => fs::read_to_string(path)?,
+        None => String::new(),
+    };
+
+    let package = Package {
+        meta: problem_package::Meta {
+            title: v.title,
+            difficulty: 0,
+            // same scale as quoj2mdoj
+            time: v.time * 1000,
+            memory: v.memory * 1024 * 1024,
+            content,
+            match_rule: grpc::MatchRule::from(v.match_rule).into(),
+            order: 0.0,
+            tags: v.tags,
+            input_file: None,
+            output_file: None,
+            checker: None,
+        },
+        testcases,
+    };
+    package.write(File::create(v.output)?)
+}
+
+/// compare names with runs of digits compared by value
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+    loop {
+        let (Some(x), Some(y)) = (a.chars().next(), b.chars().next()) else {
+            return a.len().cmp(&b.len());
+        };
+        if x.is_ascii_digit() && y.is_ascii_digit() {
+            let (na, ra) = split_digits(a);
+            let (nb, rb) = split_digits(b);
+            let ord = na
+                .trim_start_matches('0')
+                .len()
+                .cmp(&nb.trim_start_matches('0').len())
+                .then_with(|| na.trim_start_matches('0').cmp(nb.trim_start_matches('0')));
+            if ord != Ordering::Equal {
+                return ord;
+            }
+            (a, b) = (ra, rb);
+        } else {
+            if x != y {
+                return x.cmp(&y);
+            }
+            (a, b) = (&a[x.len_utf8()..], &b[y.len_utf8()..]);
+        }
+    }
+}
+
+fn split_digits(s: &str) -> (&str, &str) {
+    s.split_at(s.find(|x: char| !x.is_ascii_digit()).unwrap_or(s.len()))
+}
@@ -0,0 +1,41 @@
+use std::{fs::File, path::PathBuf};
+
+use crate::{
+    grpc::{self, WithToken},
+    package::Package,
+};
+use anyhow::Result;
+use clap::Parser;
+use futures::TryStreamExt;
+
+/// max size of a message, the same as testcase limit of backend
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024 * 16;
+
+#[derive(Debug, Parser)]
+pub struct Export {
+    #[arg(long)]
+    mdoj_api: String,
+    #[arg(long)]
+    mdoj_session: String,
+
+    #[arg(long)]
+    problem_id: i32,
+    /// path of the package to write
+    #[arg(long)]
+    output: PathBuf,
+}
+
+pub async fn export(v: Export) -> Result<()> {
+    let mut client = grpc::problem_client::ProblemClient::connect(v.mdoj_api)
+        .await?
+        .max_decoding_message_size(MAX_MESSAGE_SIZE);
+
+    let parts: Vec<_> = client
+        .export(grpc::Id { id: v.problem_id }.with_token(&v.mdoj_session))
+        .await?
+        .into_inner()
+        .try_collect()
+        .await?;
+
+    Package::from_parts(parts)?.write(File::create(v.output)?)
+}
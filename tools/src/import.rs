@@ -0,0 +1,73 @@
+use std::{fs::File, path::PathBuf};
+
+use crate::{
+    grpc::{self, WithToken},
+    package::Package,
+};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+
+/// size of each chunk, smaller than message limit of backend
+const CHUNK_SIZE: usize = 1024 * 1024 * 8;
+
+#[derive(Debug, Parser)]
+pub struct Import {
+    #[arg(long)]
+    mdoj_api: String,
+    #[arg(long)]
+    mdoj_session: String,
+
+    /// path of the package to read
+    #[arg(long)]
+    package: PathBuf,
+    /// publish the problem after import
+    #[arg(long)]
+    publish: bool,
+}
+
+pub async fn import(v: Import) -> Result<()> {
+    let package = Package::read(File::open(v.package)?)?;
+    let mut client = grpc::problem_client::ProblemClient::connect(v.mdoj_api).await?;
+
+    let mut upload_id = None;
+    for chunk in package.encode().chunks(CHUNK_SIZE) {
+        let res = client
+            .upload_package(
+                grpc::UploadChunkRequest {
+                    upload_id,
+                    chunk: chunk.to_vec(),
+                }
+                .with_token(&v.mdoj_session),
+            )
+            .await?
+            .into_inner();
+        upload_id = Some(res.upload_id);
+    }
+    let upload_id = upload_id.ok_or_else(|| anyhow!("empty package"))?;
+
+    let id = client
+        .import(
+            grpc::ImportProblemRequest {
+                upload_id,
+                request_id: None,
+            }
+            .with_token(&v.mdoj_session),
+        )
+        .await?
+        .into_inner()
+        .id;
+
+    if v.publish {
+        client
+            .publish(
+                grpc::PublishRequest {
+                    id,
+                    request_id: None,
+                }
+                .with_token(&v.mdoj_session),
+            )
+            .await?;
+    }
+    println!("{id}");
+    Ok(())
+}
@@ -1,4 +1,9 @@
+mod dir2mdoj;
+mod export;
 mod grpc;
+mod import;
+mod package;
+mod polygon2mdoj;
 mod quoj;
 mod quoj2mdoj;
 
@@ -8,6 +13,10 @@ use clap::Parser;
 #[derive(Debug, Parser)]
 enum Cli {
     Quoj2mdoj(quoj2mdoj::Quoj2mdoj),
+    Polygon2mdoj(polygon2mdoj::Polygon2mdoj),
+    Dir2mdoj(dir2mdoj::Dir2mdoj),
+    Export(export::Export),
+    Import(import::Import),
 }
 
 #[tokio::main]
@@ -15,6 +24,10 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
     match cli {
         Cli::Quoj2mdoj(v) => quoj2mdoj::quoj2mdoj(v).await?,
+        Cli::Polygon2mdoj(v) => polygon2mdoj::polygon2mdoj(v)?,
+        Cli::Dir2mdoj(v) => dir2mdoj::dir2mdoj(v)?,
+        Cli::Export(v) => export::export(v).await?,
+        Cli::Import(v) => import::import(v).await?,
     };
 
     Ok(())
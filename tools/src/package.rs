@@ -0,0 +1,232 @@
+//! native problem package
+//!
+//! A package is a zip with following layout:
+//!
+//! ```text
+//! problem.json    -- see [`Manifest`]
+//! statement.md
+//! checker/source  -- optional
+//! tests/1.in
+//! tests/1.out
+//! ...
+//! ```
+use std::io::{Read, Seek, Write};
+
+use crate::grpc::{self, problem_package};
+use anyhow::{anyhow, Result};
+use prost::Message;
+use serde::{Deserialize, Serialize};
+use zip::{write::SimpleFileOptions, ZipArchive, ZipWriter};
+
+const MANIFEST: &str = "problem.json";
+const STATEMENT: &str = "statement.md";
+const CHECKER: &str = "checker/source";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchRule {
+    Exactly,
+    #[default]
+    IgnoreSnl,
+    SkipSnl,
+}
+
+impl From<MatchRule> for grpc::MatchRule {
+    fn from(value: MatchRule) -> Self {
+        match value {
+            MatchRule::Exactly => grpc::MatchRule::MatchruleExactly,
+            MatchRule::IgnoreSnl => grpc::MatchRule::MatchruleIgnoreSnl,
+            MatchRule::SkipSnl => grpc::MatchRule::MatchruleSkipSnl,
+        }
+    }
+}
+
+impl From<grpc::MatchRule> for MatchRule {
+    fn from(value: grpc::MatchRule) -> Self {
+        match value {
+            grpc::MatchRule::MatchruleExactly => MatchRule::Exactly,
+            grpc::MatchRule::MatchruleIgnoreSnl => MatchRule::IgnoreSnl,
+            grpc::MatchRule::MatchruleSkipSnl => MatchRule::SkipSnl,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checker {
+    pub lang_uid: String,
+    pub path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Testcase {
+    pub input: String,
+    pub output: String,
+    pub score: u32,
+}
+
+/// `problem.json`, limits are in the same unit as `Problem.Create`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub title: String,
+    #[serde(default)]
+    pub difficulty: u32,
+    pub time: u64,
+    pub memory: u64,
+    #[serde(default)]
+    pub match_rule: MatchRule,
+    #[serde(default)]
+    pub order: f32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub input_file: Option<String>,
+    #[serde(default)]
+    pub output_file: Option<String>,
+    #[serde(default)]
+    pub checker: Option<Checker>,
+    pub testcases: Vec<Testcase>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Package {
+    pub meta: problem_package::Meta,
+    pub testcases: Vec<problem_package::Testcase>,
+}
+
+impl Package {
+    pub fn read(reader: impl Read + Seek) -> Result<Self> {
+        let mut zip = ZipArchive::new(reader)?;
+        let manifest: Manifest = serde_json::from_slice(&read_file(&mut zip, MANIFEST)?)?;
+        let content = String::from_utf8(read_file(&mut zip, STATEMENT)?)?;
+
+        let checker = match manifest.checker {
+            Some(checker) => Some(problem_package::Checker {
+                code: read_file(&mut zip, &checker.path)?,
+                lang_uid: checker.lang_uid,
+            }),
+            None => None,
+        };
+
+        let testcases = manifest
+            .testcases
+            .iter()
+            .map(|x| {
+                Ok(problem_package::Testcase {
+                    input: read_file(&mut zip, &x.input)?,
+                    output: read_file(&mut zip, &x.output)?,
+                    score: x.score,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let meta = problem_package::Meta {
+            title: manifest.title,
+            difficulty: manifest.difficulty,
+            time: manifest.time,
+            memory: manifest.memory,
+            content,
+            match_rule: grpc::MatchRule::from(manifest.match_rule).into(),
+            order: manifest.order,
+            tags: manifest.tags,
+            input_file: manifest.input_file,
+            output_file: manifest.output_file,
+            checker,
+        };
+        Ok(Self { meta, testcases })
+    }
+    pub fn write(&self, writer: impl Write + Seek) -> Result<()> {
+        let mut zip = ZipWriter::new(writer);
+        let options = SimpleFileOptions::default();
+
+        let mut testcases = Vec::with_capacity(self.testcases.len());
+        for (i, testcase) in self.testcases.iter().enumerate() {
+            let (input, output) = (
+                format!("tests/{}.in", i + 1),
+                format!("tests/{}.out", i + 1),
+            );
+            zip.start_file(input.as_str(), options)?;
+            zip.write_all(&testcase.input)?;
+            zip.start_file(output.as_str(), options)?;
+            zip.write_all(&testcase.output)?;
+            testcases.push(Testcase {
+                input,
+                output,
+                score: testcase.score,
+            });
+        }
+
+        let checker = match &self.meta.checker {
+            Some(checker) => {
+                zip.start_file(CHECKER, options)?;
+                zip.write_all(&checker.code)?;
+                Some(Checker {
+                    lang_uid: checker.lang_uid.clone(),
+                    path: CHECKER.to_string(),
+                })
+            }
+            None => None,
+        };
+
+        zip.start_file(STATEMENT, options)?;
+        zip.write_all(self.meta.content.as_bytes())?;
+
+        let manifest = Manifest {
+            title: self.meta.title.clone(),
+            difficulty: self.meta.difficulty,
+            time: self.meta.time,
+            memory: self.meta.memory,
+            match_rule: self.meta.match_rule().into(),
+            order: self.meta.order,
+            tags: self.meta.tags.clone(),
+            input_file: self.meta.input_file.clone(),
+            output_file: self.meta.output_file.clone(),
+            checker,
+            testcases,
+        };
+        zip.start_file(MANIFEST, options)?;
+        serde_json::to_writer_pretty(&mut zip, &manifest)?;
+
+        zip.finish()?;
+        Ok(())
+    }
+    /// encode as length-delimited messages uploaded by `Problem.UploadPackage`
+    pub fn encode(self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        std::iter::once(problem_package::Part::Meta(self.meta))
+            .chain(
+                self.testcases
+                    .into_iter()
+                    .map(problem_package::Part::Testcase),
+            )
+            .map(|x| grpc::ProblemPackage { part: Some(x) })
+            .for_each(|x| x.encode_length_delimited(&mut buf).unwrap());
+        buf
+    }
+    /// collect messages of `Problem.Export`
+    pub fn from_parts(parts: impl IntoIterator<Item = grpc::ProblemPackage>) -> Result<Self> {
+        let mut parts = parts.into_iter().map(|x| x.part);
+        let Some(Some(problem_package::Part::Meta(meta))) = parts.next() else {
+            return Err(anyhow!("package doesn't start with meta"));
+        };
+        let testcases = parts
+            .map(|x| match x {
+                Some(problem_package::Part::Testcase(x)) => Ok(x),
+                _ => Err(anyhow!("unexpected part after meta")),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { meta, testcases })
+    }
+}
+
+/// split `total` score evenly, the remainder goes to the first testcases
+pub fn even_score(total: u32, count: usize) -> impl Iterator<Item = u32> {
+    let count = count as u32;
+    (0..count).map(move |i| total / count + u32::from(i < total % count))
+}
+
+pub fn read_file<R: Read + Seek>(zip: &mut ZipArchive<R>, name: &str) -> Result<Vec<u8>> {
+    let mut file = zip.by_name(name).map_err(|err| anyhow!("{name}: {err}"))?;
+    let mut buf = Vec::with_capacity(file.size() as usize);
+    file.read_to_end(&mut buf)?;
+    Ok(buf)
+}
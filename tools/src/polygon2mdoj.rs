@@ -0,0 +1,227 @@
+//! convert polygon full package to native package
+//!
+//! Only the `tests` testset is converted, and tests must be generated
+//! (download the "full" package, not the "standard" one).
+use std::{fs::File, path::PathBuf};
+
+use crate::{
+    grpc::{self, problem_package},
+    package::{self, read_file, Package},
+};
+use anyhow::{anyhow, Result};
+use clap::Parser;
+use quick_xml::events::{BytesStart, Event};
+use zip::ZipArchive;
+
+#[derive(Debug, Parser)]
+pub struct Polygon2mdoj {
+    /// path of polygon package
+    #[arg(long)]
+    package: PathBuf,
+    /// path of the package to write
+    #[arg(long)]
+    output: PathBuf,
+
+    /// language of statement, the first one is used if absent
+    #[arg(long)]
+    language: Option<String>,
+    /// language uid of the checker in mdoj, the checker is dropped if absent
+    #[arg(long)]
+    checker_lang: Option<String>,
+    /// score of all tests if tests don't have points
+    #[arg(long, default_value_t = 100)]
+    total_score: u32,
+}
+
+#[derive(Debug, Default)]
+struct Problem {
+    names: Vec<(String, String)>,
+    time_limit: u64,
+    memory_limit: u64,
+    input_file: Option<String>,
+    output_file: Option<String>,
+    input_pattern: String,
+    answer_pattern: String,
+    points: Vec<Option<f64>>,
+    tags: Vec<String>,
+    checker: Option<String>,
+}
+
+pub fn polygon2mdoj(v: Polygon2mdoj) -> Result<()> {
+    let mut zip = ZipArchive::new(File::open(&v.package)?)?;
+    let problem = parse_problem(&String::from_utf8(read_file(&mut zip, "problem.xml")?)?)?;
+
+    let (language, title) = match &v.language {
+        Some(language) => problem
+            .names
+            .iter()
+            .find(|(x, _)| x == language)
+            .cloned()
+            .ok_or_else(|| anyhow!("no statement in {language}"))?,
+        None => problem
+            .names
+            .first()
+            .cloned()
+            .ok_or_else(|| anyhow!("problem has no name"))?,
+    };
+
+    let mut content = String::new();
+    for (section, heading) in [
+        ("legend", None),
+        ("input", Some("Input")),
+        ("output", Some("Output")),
+        ("notes", Some("Notes")),
+    ] {
+        let Ok(text) = read_file(
+            &mut zip,
+            &format!("statement-sections/{language}/{section}.tex"),
+        ) else {
+            continue;
+        };
+        if let Some(heading) = heading {
+            content.push_str(&format!("\n\n## {heading}\n\n"));
+        }
+        content.push_str(String::from_utf8(text)?.trim());
+    }
+
+    let checker = match (problem.checker, v.checker_lang) {
+        (Some(path), Some(lang_uid)) => Some(problem_package::Checker {
+            lang_uid,
+            code: read_file(&mut zip, &path)?,
+        }),
+        _ => None,
+    };
+
+    let even = package::even_score(v.total_score, problem.points.len()).collect::<Vec<_>>();
+    let use_points = problem.points.iter().all(Option::is_some);
+    let mut testcases = Vec::with_capacity(problem.points.len());
+    for (i, points) in problem.points.iter().enumerate() {
+        testcases.push(problem_package::Testcase {
+            input: read_file(&mut zip, &format_pattern(&problem.input_pattern, i + 1))?,
+            output: read_file(&mut zip, &format_pattern(&problem.answer_pattern, i + 1))?,
+            score: match points {
+                Some(x) if use_points => x.round() as u32,
+                _ => even[i],
+            },
+        });
+    }
+
+    let package = Package {
+        meta: problem_package::Meta {
+            title,
+            difficulty: 0,
+            // same scale as quoj2mdoj
+            time: problem.time_limit * 1000,
+            memory: problem.memory_limit,
+            content,
+            match_rule: grpc::MatchRule::MatchruleIgnoreSnl.into(),
+            order: 0.0,
+            tags: problem.tags,
+            input_file: problem.input_file,
+            output_file: problem.output_file,
+            checker,
+        },
+        testcases,
+    };
+    package.write(File::create(v.output)?)
+}
+
+fn parse_problem(xml: &str) -> Result<Problem> {
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut problem = Problem::default();
+    let mut path: Vec<String> = Vec::new();
+    let mut testset = None;
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(e) => {
+                on_element(&mut problem, &path, &mut testset, &e)?;
+                path.push(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+            }
+            Event::Empty(e) => on_element(&mut problem, &path, &mut testset, &e)?,
+            Event::End(_) => {
+                if path.pop().as_deref() == Some("testset") {
+                    testset = None;
+                }
+            }
+            Event::Text(e) => {
+                if testset.as_deref() != Some("tests") {
+                    continue;
+                }
+                let text = e.unescape()?;
+                let text = text.trim();
+                match path.last().map(String::as_str) {
+                    Some("time-limit") => problem.time_limit = text.parse()?,
+                    Some("memory-limit") => problem.memory_limit = text.parse()?,
+                    Some("input-path-pattern") => problem.input_pattern = text.to_string(),
+                    Some("answer-path-pattern") => problem.answer_pattern = text.to_string(),
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    if problem.input_pattern.is_empty() || problem.answer_pattern.is_empty() {
+        return Err(anyhow!("testset `tests` not found"));
+    }
+    Ok(problem)
+}
+
+fn on_element(
+    problem: &mut Problem,
+    path: &[String],
+    testset: &mut Option<String>,
+    e: &BytesStart,
+) -> Result<()> {
+    let attr = |name: &str| -> Result<Option<String>> {
+        Ok(match e.try_get_attribute(name)? {
+            Some(x) => Some(x.unescape_value()?.into_owned()),
+            None => None,
+        })
+    };
+    let parent = path.last().map(String::as_str);
+    match (parent, e.name().as_ref()) {
+        (Some("names"), b"name") => {
+            if let (Some(language), Some(value)) = (attr("language")?, attr("value")?) {
+                problem.names.push((language, value));
+            }
+        }
+        (_, b"judging") => {
+            // empty means stdin/stdout
+            problem.input_file = attr("input-file")?.filter(|x| !x.is_empty());
+            problem.output_file = attr("output-file")?.filter(|x| !x.is_empty());
+        }
+        (Some("judging"), b"testset") => *testset = attr("name")?,
+        (Some("tests"), b"test") if testset.as_deref() == Some("tests") => {
+            problem
+                .points
+                .push(attr("points")?.map(|x| x.parse()).transpose()?);
+        }
+        (Some("tags"), b"tag") => problem.tags.extend(attr("value")?),
+        (Some("checker"), b"source") => problem.checker = attr("path")?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// format printf-like pattern such as `tests/%02d`
+fn format_pattern(pattern: &str, index: usize) -> String {
+    let Some(start) = pattern.find('%') else {
+        return pattern.to_string();
+    };
+    let Some(len) = pattern[start..].find('d') else {
+        return pattern.to_string();
+    };
+    let width: usize = pattern[start + 1..start + len]
+        .trim_start_matches('0')
+        .parse()
+        .unwrap_or_default();
+    format!(
+        "{}{:0width$}{}",
+        &pattern[..start],
+        index,
+        &pattern[start + len + 1..]
+    )
+}